@@ -1,6 +1,14 @@
 use dashmap::DashMap;
 use once_cell::sync::OnceCell;
-use rocket::{get, serde::json::Json};
+use rocket::{
+    get,
+    http::{Cookie, CookieJar, Status},
+    post,
+    request::{FromRequest, Outcome},
+    serde::json::Json,
+    Request,
+};
+use uuid::Uuid;
 
 use squire_sdk::accounts::{
     AccountId, AccountIdentifier, GetAllUsersResponse, GetOrgResponse, GetUserResponse, OrgAccount,
@@ -10,6 +18,65 @@ use squire_sdk::accounts::{
 pub static USERS_MAP: OnceCell<DashMap<AccountId, UserAccount>> = OnceCell::new();
 pub static ORGS_MAP: OnceCell<DashMap<AccountId, OrgAccount>> = OnceCell::new();
 
+/// The name of the private cookie that holds the id of the logged-in user
+pub const USER_COOKIE: &str = "user_id";
+
+/// A request guard for the user that sent the request. The user's id is read from a private
+/// cookie, which can only have been set by this server, so the id can be trusted.
+pub struct AuthUser(pub UserAccount);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthUser {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let account = req
+            .cookies()
+            .get_private(USER_COOKIE)
+            .and_then(|c| Uuid::parse_str(c.value()).ok())
+            .and_then(|id| {
+                USERS_MAP
+                    .get()
+                    .unwrap()
+                    .get(&AccountId(id))
+                    .map(|a| a.clone())
+            });
+        match account {
+            Some(account) => Outcome::Success(AuthUser(account)),
+            None => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/// Finds a user by their id or account name
+fn find_user(ident: AccountIdentifier) -> Option<UserAccount> {
+    let users = USERS_MAP.get().unwrap();
+    match ident {
+        AccountIdentifier::Id(id) => users.get(&id).map(|a| a.clone()),
+        AccountIdentifier::Name(name) => users
+            .iter()
+            .find(|a| a.account_name == name)
+            .map(|a| a.clone()),
+    }
+}
+
+/// Logs in as the given user by storing their id in the user cookie. The account is returned if
+/// it exists. Accounts don't have credentials yet, so any known account can be logged into.
+#[post("/login", format = "json", data = "<ident>")]
+pub fn login(cookies: &CookieJar<'_>, ident: Json<AccountIdentifier>) -> GetUserResponse {
+    let account = find_user(ident.0);
+    if let Some(account) = &account {
+        cookies.add_private(Cookie::new(USER_COOKIE, account.external_id.0.to_string()));
+    }
+    GetUserResponse::new(account)
+}
+
+/// Removes the user cookie
+#[post("/logout")]
+pub fn logout(cookies: &CookieJar<'_>) {
+    cookies.remove_private(Cookie::from(USER_COOKIE));
+}
+
 #[get("/users", format = "json", data = "<ident>")]
 pub fn users(ident: Json<AccountIdentifier>) -> GetUserResponse {
     match ident.0 {
@@ -1,5 +1,5 @@
 use dashmap::DashMap;
-use rocket::{get, routes};
+use rocket::{get, routes, Build, Rocket};
 use squire_sdk::accounts::{AccountId, UserAccount};
 use uuid::Uuid;
 
@@ -17,22 +17,18 @@ fn world() -> &'static str {
     "Hello, world!"
 }
 
-#[rocket::main]
-async fn main() -> Result<(), rocket::Error> {
+/// Creates the maps that hold all accounts and tournaments
+fn init_maps() {
     let _ = USERS_MAP.set(DashMap::new());
     let _ = ORGS_MAP.set(DashMap::new());
     let _ = TOURNS_MAP.set(DashMap::new());
-    let id = AccountId(Uuid::new_v4());
-    let account = UserAccount {
-        external_id: id.clone(),
-        display_name: "Tyler Bloom".to_string(),
-        account_name: "TylerBloom".to_string(),
-    };
-    println!("{account:?}");
-    USERS_MAP.get().unwrap().insert(id, account);
-    let _rocket = rocket::build()
+}
+
+/// Builds the server with all of its routes mounted
+fn build() -> Rocket<Build> {
+    rocket::build()
         .mount("/hello", routes![world])
-        .mount("/accounts", routes![users, all_users, orgs])
+        .mount("/accounts", routes![users, all_users, orgs, login, logout])
         .mount(
             "/tournaments",
             routes![
@@ -40,8 +36,13 @@ async fn main() -> Result<(), rocket::Error> {
                 get_tournament,
                 get_all_tournaments,
                 get_standings,
+                get_standings_v2,
+                get_rank_history,
                 list_ops,
+                apply_op,
+                audit,
                 sync,
+                sync_import,
                 rollback
             ],
         )
@@ -60,8 +61,204 @@ async fn main() -> Result<(), rocket::Error> {
                 get_latest_player_match,
             ],
         )
-        .launch()
-        .await?;
+}
+
+#[rocket::main]
+async fn main() -> Result<(), rocket::Error> {
+    init_maps();
+    let id = AccountId(Uuid::new_v4());
+    let account = UserAccount {
+        external_id: id.clone(),
+        display_name: "Tyler Bloom".to_string(),
+        account_name: "TylerBloom".to_string(),
+    };
+    println!("{account:?}");
+    USERS_MAP.get().unwrap().insert(id, account);
+    let _rocket = build().launch().await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use rocket::{http::Status, local::blocking::Client};
+    use squire_lib::{
+        operations::{OpData, OpResult, TournOp},
+        scoring::Standings,
+        settings::TournamentSetting,
+        standard_scoring::StandardScore,
+        tournament::{
+            ScoringPreset, Tournament, TournamentIdentifier, TournamentPreset, TournamentScore,
+        },
+    };
+    use squire_sdk::{
+        accounts::AccountIdentifier,
+        tournaments::{ApplyOpRequest, StandingsRequest, TournamentCreateRequest},
+    };
+
+    use super::*;
+
+    fn add_user(name: &str) -> UserAccount {
+        init_maps();
+        let account = UserAccount {
+            external_id: AccountId(Uuid::new_v4()),
+            display_name: name.to_string(),
+            account_name: name.to_string(),
+        };
+        USERS_MAP
+            .get()
+            .unwrap()
+            .insert(account.external_id.clone(), account.clone());
+        account
+    }
+
+    fn client() -> (Client, UserAccount) {
+        let account = add_user("admin");
+        (Client::tracked(build()).unwrap(), account)
+    }
+
+    fn create_request() -> TournamentCreateRequest {
+        TournamentCreateRequest {
+            name: "Test".to_string(),
+            preset: TournamentPreset::Swiss,
+            format: "Pioneer".to_string(),
+        }
+    }
+
+    /// Logs in as the given user and creates a tournament
+    fn create_tournament(client: &Client, account: &UserAccount) -> Tournament {
+        client
+            .post("/accounts/login")
+            .json(&AccountIdentifier::Id(account.external_id.clone()))
+            .dispatch();
+        client
+            .post("/tournaments/create")
+            .json(&create_request())
+            .dispatch()
+            .into_json()
+            .unwrap()
+    }
+
+    fn apply_op(client: &Client, tourn: &Tournament, op: TournOp) -> Option<OpResult> {
+        let request = ApplyOpRequest {
+            ident: TournamentIdentifier::Id(tourn.id),
+            op,
+            reason: None,
+        };
+        client
+            .post("/tournaments/manage/apply")
+            .json(&request)
+            .dispatch()
+            .into_json()
+            .unwrap()
+    }
+
+    #[test]
+    fn create_a_tournament_and_apply_an_op() {
+        let (client, account) = client();
+        let resp = client
+            .post("/tournaments/create")
+            .json(&create_request())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Unauthorized);
+
+        let resp = client
+            .post("/accounts/login")
+            .json(&AccountIdentifier::Id(account.external_id.clone()))
+            .dispatch();
+        assert_eq!(
+            resp.into_json::<Option<UserAccount>>(),
+            Some(Some(account.clone()))
+        );
+
+        let tourn: Tournament = client
+            .post("/tournaments/create")
+            .json(&create_request())
+            .dispatch()
+            .into_json()
+            .unwrap();
+        assert!(tourn.admins.contains_key(&account.external_id));
+
+        let request = ApplyOpRequest {
+            ident: TournamentIdentifier::Id(tourn.id),
+            op: TournOp::RegisterPlayer("Player".to_string()),
+            reason: None,
+        };
+        let resp: Option<OpResult> = client
+            .post("/tournaments/manage/apply")
+            .json(&request)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        assert!(matches!(resp, Some(Ok(OpData::RegisterPlayer(_)))));
+
+        client.post("/accounts/logout").dispatch();
+        let resp = client
+            .post("/tournaments/manage/apply")
+            .json(&request)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Unauthorized);
+
+        // Anyone that is logged in can register themselves
+        let player = add_user("player");
+        client
+            .post("/accounts/login")
+            .json(&AccountIdentifier::Name(player.account_name.clone()))
+            .dispatch();
+        let request = ApplyOpRequest {
+            ident: TournamentIdentifier::Id(tourn.id),
+            op: TournOp::RegisterPlayerAccount(player),
+            reason: None,
+        };
+        let resp: Option<OpResult> = client
+            .post("/tournaments/manage/apply")
+            .json(&request)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        assert!(matches!(resp, Some(Ok(OpData::RegisterPlayer(_)))));
+    }
+
+    #[test]
+    fn standard_standings_keep_their_shape() {
+        let (client, account) = client();
+        let tourn = create_tournament(&client, &account);
+        for name in ["Alice", "Bob"] {
+            apply_op(&client, &tourn, TournOp::RegisterPlayer(name.to_string()));
+        }
+        let request = StandingsRequest {
+            ident: TournamentIdentifier::Id(tourn.id),
+        };
+        let old: Option<Standings<StandardScore>> = client
+            .get("/tournaments/standings")
+            .json(&request)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        assert_eq!(old.unwrap().scores.len(), 2);
+
+        // Other scoring systems can only be gotten through the versioned route
+        let preset = TournamentSetting::ScoringPreset(ScoringPreset::Buchholz);
+        let resp = apply_op(&client, &tourn, TournOp::UpdateTournSetting(preset));
+        assert!(matches!(resp, Some(Ok(_))));
+        let old: Option<Standings<StandardScore>> = client
+            .get("/tournaments/standings")
+            .json(&request)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        assert!(old.is_none());
+        let new: Option<Standings<TournamentScore>> = client
+            .get("/tournaments/v2/standings")
+            .json(&request)
+            .dispatch()
+            .into_json()
+            .unwrap();
+        let new = new.unwrap();
+        assert_eq!(new.scores.len(), 2);
+        assert!(new
+            .scores
+            .iter()
+            .all(|(_, s)| matches!(s, TournamentScore::Chess(_))));
+    }
+}
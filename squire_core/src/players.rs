@@ -2,7 +2,7 @@ use dashmap::DashMap;
 use once_cell::sync::OnceCell;
 use rocket::{get, post, serde::json::Json};
 
-use squire_lib::tournament::{Tournament, TournamentId, TournamentIdentifier};
+use squire_lib::tournament::TournamentIdentifier;
 use squire_sdk::players::{
     GetAllDecksRequest, GetAllDecksResponse, GetAllPlayerDecksRequest, GetAllPlayerDecksResponse,
     GetDeckRequest, GetDeckResponse, GetLatestPlayerMatchRequest, GetLatestPlayerMatchResponse,
@@ -17,11 +17,13 @@ use crate::tournaments::TOURNS_MAP;
 pub fn get_player(data: Json<GetPlayerRequest>) -> GetPlayerResponse {
     match data.0.tourn {
         TournamentIdentifier::Id(id) => {
-            let digest = TOURNS_MAP
-                .get()
-                .unwrap()
-                .get(&id)
-                .map(|tourn| tourn.get_player(&data.0.player).map(|p| p.clone()).ok());
+            let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                tourn
+                    .get_state()
+                    .get_player(&data.0.player)
+                    .map(|p| p.clone())
+                    .ok()
+            });
             GetPlayerResponse::new(digest)
         }
         TournamentIdentifier::Name(_name) => {
@@ -35,6 +37,7 @@ pub fn get_all_players(data: Json<GetMultiplePlayersRequest>) -> GetMultiplePlay
     match data.0.tourn {
         TournamentIdentifier::Id(id) => {
             let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                let tourn = tourn.get_state();
                 tourn
                     .player_reg
                     .players
@@ -55,6 +58,7 @@ pub fn get_active_players(data: Json<GetMultiplePlayersRequest>) -> GetMultipleP
     match data.0.tourn {
         TournamentIdentifier::Id(id) => {
             let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                let tourn = tourn.get_state();
                 tourn
                     .player_reg
                     .players
@@ -79,7 +83,7 @@ pub fn get_player_count(data: Json<GetPlayerCountRequest>) -> GetPlayerCountResp
                 .get()
                 .unwrap()
                 .get(&id)
-                .map(|tourn| tourn.player_reg.players.len() as u64);
+                .map(|tourn| tourn.get_state().player_reg.players.len() as u64);
             GetPlayerCountResponse::new(digest)
         }
         TournamentIdentifier::Name(_name) => {
@@ -96,7 +100,7 @@ pub fn get_active_player_count(data: Json<GetPlayerCountRequest>) -> GetPlayerCo
                 .get()
                 .unwrap()
                 .get(&id)
-                .map(|tourn| tourn.player_reg.active_player_count() as u64);
+                .map(|tourn| tourn.get_state().player_reg.active_player_count() as u64);
             GetPlayerCountResponse::new(digest)
         }
         TournamentIdentifier::Name(_name) => {
@@ -110,6 +114,7 @@ pub fn get_player_matches(data: Json<GetPlayerMatchesRequest>) -> GetPlayerMatch
     match data.0.tourn {
         TournamentIdentifier::Id(id) => {
             let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                let tourn = tourn.get_state();
                 tourn.get_player(&data.0.player).ok().map(|p| {
                     tourn
                         .round_reg
@@ -136,6 +141,7 @@ pub fn get_latest_player_match(
         TournamentIdentifier::Id(id) => {
             // TODO: This is techincally incorrect. Fix
             let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                let tourn = tourn.get_state();
                 tourn.get_player(&data.0.player).ok().map(|p| {
                     tourn
                         .round_reg
@@ -158,6 +164,7 @@ pub fn get_player_deck(data: Json<GetDeckRequest>) -> GetDeckResponse {
     match data.0.tourn {
         TournamentIdentifier::Id(id) => {
             let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                let tourn = tourn.get_state();
                 tourn
                     .get_player(&data.0.player)
                     .ok()
@@ -176,6 +183,7 @@ pub fn get_all_decks(data: Json<GetAllDecksRequest>) -> GetAllDecksResponse {
     match data.0.tourn {
         TournamentIdentifier::Id(id) => {
             let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                let tourn = tourn.get_state();
                 tourn
                     .player_reg
                     .players
@@ -196,6 +204,7 @@ pub fn get_all_player_decks(data: Json<GetAllPlayerDecksRequest>) -> GetAllPlaye
     match data.0.tourn {
         TournamentIdentifier::Id(id) => {
             let digest = TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                let tourn = tourn.get_state();
                 tourn
                     .get_player(&data.0.player)
                     .ok()
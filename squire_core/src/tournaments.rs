@@ -2,29 +2,55 @@ use dashmap::DashMap;
 use once_cell::sync::OnceCell;
 use rocket::{get, post, serde::json::Json};
 
-use squire_lib::tournament::{Tournament, TournamentId, TournamentIdentifier};
+use squire_lib::{
+    error::TournamentError,
+    operations::{OpAuthor, TournOp},
+    scoring::Standings,
+    tournament::{TournamentId, TournamentIdentifier, TournamentScore},
+    tournament_manager::TournamentManager,
+};
 use squire_sdk::tournaments::{
-    self, CreateResponse, GetAllResponse, GetResponse, ListOpsRequest, ListOpsResponse,
-    RollbackRequest, RollbackResponse, StandingsRequest, StandingsResponse, SyncRequest,
-    SyncResponse, TournamentCreateRequest, TournamentGetRequest,
+    self, ApplyOpRequest, ApplyOpResponse, AuditRequest, AuditResponse, CreateResponse,
+    GetAllResponse, GetResponse, ListOpsRequest, ListOpsResponse, RankHistoryRequest,
+    RankHistoryResponse, RollbackRequest, RollbackResponse, StandingsRequest, StandingsResponse,
+    StandingsV2Response, SyncImportRequest, SyncImportResponse, SyncRequest, SyncResponse,
+    TournamentCreateRequest, TournamentGetRequest,
 };
 
-pub static TOURNS_MAP: OnceCell<DashMap<TournamentId, Tournament>> = OnceCell::new();
+use crate::accounts::AuthUser;
+
+pub static TOURNS_MAP: OnceCell<DashMap<TournamentId, TournamentManager>> = OnceCell::new();
+
+/// The full op log shows every change made to a tournament, so only its judges and admins can
+/// read it
+fn check_staff(tourn: &TournamentManager, user: &AuthUser) -> Result<(), TournamentError> {
+    if tourn.get_state().is_staff(&user.0.external_id) {
+        Ok(())
+    } else {
+        Err(TournamentError::InsufficientPermissions)
+    }
+}
 
 #[post("/create", format = "json", data = "<data>")]
-pub fn create_tournament(data: Json<TournamentCreateRequest>) -> CreateResponse {
-    let tourn = Tournament::from_preset(data.0.name, data.0.preset, data.0.format);
-    let id = tourn.id.clone();
-    TOURNS_MAP.get().unwrap().insert(id, tourn.clone());
-    tournaments::CreateResponse::new(tourn)
+pub fn create_tournament(user: AuthUser, data: Json<TournamentCreateRequest>) -> CreateResponse {
+    let mut tourn = TournamentManager::from_preset(data.0.name, data.0.preset, data.0.format);
+    // The creator is the first admin. Only the server can submit this as the system.
+    let _ = tourn.apply_op(TournOp::RegisterAdmin(user.0), OpAuthor::System, None);
+    let digest = tourn.get_state().clone();
+    TOURNS_MAP.get().unwrap().insert(digest.id, tourn);
+    tournaments::CreateResponse::new(digest)
 }
 
 #[get("/get", format = "json", data = "<data>")]
 pub fn get_tournament(data: Json<TournamentGetRequest>) -> GetResponse {
     match data.0.ident {
-        TournamentIdentifier::Id(id) => {
-            tournaments::GetResponse::new(TOURNS_MAP.get().unwrap().get(&id).map(|a| a.clone()))
-        }
+        TournamentIdentifier::Id(id) => tournaments::GetResponse::new(
+            TOURNS_MAP
+                .get()
+                .unwrap()
+                .get(&id)
+                .map(|a| a.get_state().clone()),
+        ),
         TournamentIdentifier::Name(_name) => {
             todo!("Yet to be impl-ed");
         }
@@ -37,20 +63,46 @@ pub fn get_all_tournaments() -> GetAllResponse {
         .get()
         .unwrap()
         .iter()
-        .map(|r| (r.key().clone(), r.value().clone()))
+        .map(|r| (r.key().clone(), r.value().get_state().clone()))
         .collect();
     GetAllResponse::new(map)
 }
 
+/// Kept for clients that only know about standard scores. Tournaments with other scoring systems
+/// have no standings here.
 #[get("/standings", format = "json", data = "<data>")]
 pub fn get_standings(data: Json<StandingsRequest>) -> StandingsResponse {
     match data.0.ident {
-        TournamentIdentifier::Id(id) => StandingsResponse::new(
+        TournamentIdentifier::Id(id) => {
+            StandingsResponse::new(TOURNS_MAP.get().unwrap().get(&id).and_then(|a| {
+                let scores = a
+                    .get_state()
+                    .get_standings()
+                    .scores
+                    .into_iter()
+                    .map(|(p, s)| match s {
+                        TournamentScore::Standard(s) => Some((p, s)),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Standings::new(scores))
+            }))
+        }
+        TournamentIdentifier::Name(_name) => {
+            todo!("Yet to be impl-ed");
+        }
+    }
+}
+
+#[get("/v2/standings", format = "json", data = "<data>")]
+pub fn get_standings_v2(data: Json<StandingsRequest>) -> StandingsV2Response {
+    match data.0.ident {
+        TournamentIdentifier::Id(id) => StandingsV2Response::new(
             TOURNS_MAP
                 .get()
                 .unwrap()
                 .get(&id)
-                .map(|a| a.get_standings()),
+                .map(|a| a.get_state().get_standings()),
         ),
         TournamentIdentifier::Name(_name) => {
             todo!("Yet to be impl-ed");
@@ -58,38 +110,112 @@ pub fn get_standings(data: Json<StandingsRequest>) -> StandingsResponse {
     }
 }
 
+#[get("/standings/history", format = "json", data = "<data>")]
+pub fn get_rank_history(data: Json<RankHistoryRequest>) -> RankHistoryResponse {
+    match data.0.ident {
+        TournamentIdentifier::Id(id) => {
+            let digest = TOURNS_MAP
+                .get()
+                .unwrap()
+                .get(&id)
+                .and_then(|tourn| tourn.get_state().get_rank_history(&data.0.player).ok());
+            RankHistoryResponse::new(digest)
+        }
+        TournamentIdentifier::Name(_name) => {
+            todo!("Yet to be impl-ed");
+        }
+    }
+}
+
 #[post("/manage/list_ops", format = "json", data = "<data>")]
-pub fn list_ops(data: Json<ListOpsRequest>) -> ListOpsResponse {
+pub fn list_ops(user: AuthUser, data: Json<ListOpsRequest>) -> ListOpsResponse {
+    match data.0.ident {
+        TournamentIdentifier::Id(id) => {
+            ListOpsResponse::new(TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                check_staff(&tourn, &user)?;
+                Ok(tourn.get_op_slice())
+            }))
+        }
+        TournamentIdentifier::Name(_name) => {
+            todo!("Yet to be impl-ed");
+        }
+    }
+}
+
+#[post("/manage/apply", format = "json", data = "<data>")]
+pub fn apply_op(user: AuthUser, data: Json<ApplyOpRequest>) -> ApplyOpResponse {
     match data.0.ident {
         TournamentIdentifier::Id(id) => {
-            todo!()
+            ApplyOpResponse::new(TOURNS_MAP.get().unwrap().get_mut(&id).map(|mut tourn| {
+                let author = tourn.get_state().author_for(&user.0.external_id);
+                tourn.apply_op(data.0.op, author, data.0.reason)
+            }))
         }
         TournamentIdentifier::Name(_name) => {
-            todo!()
+            todo!("Yet to be impl-ed");
+        }
+    }
+}
+
+#[post("/manage/audit", format = "json", data = "<data>")]
+pub fn audit(user: AuthUser, data: Json<AuditRequest>) -> AuditResponse {
+    match data.0.ident {
+        TournamentIdentifier::Id(id) => {
+            AuditResponse::new(TOURNS_MAP.get().unwrap().get(&id).map(|tourn| {
+                check_staff(&tourn, &user)?;
+                Ok(tourn.audit(&data.0.filter))
+            }))
+        }
+        TournamentIdentifier::Name(_name) => {
+            todo!("Yet to be impl-ed");
         }
     }
 }
 
 #[post("/manage/sync", format = "json", data = "<data>")]
-pub fn sync(data: Json<SyncRequest>) -> SyncResponse {
+pub fn sync(user: AuthUser, data: Json<SyncRequest>) -> SyncResponse {
     match data.0.ident {
         TournamentIdentifier::Id(id) => {
-            todo!()
+            SyncResponse::new(TOURNS_MAP.get().unwrap().get_mut(&id).map(|mut tourn| {
+                let author = tourn.get_state().author_for(&user.0.external_id);
+                tourn.check_sync(&author, &data.0.sync)?;
+                Ok(tourn.start_sync(data.0.sync))
+            }))
         }
         TournamentIdentifier::Name(_name) => {
-            todo!()
+            todo!("Yet to be impl-ed");
+        }
+    }
+}
+
+#[post("/manage/sync_import", format = "json", data = "<data>")]
+pub fn sync_import(user: AuthUser, data: Json<SyncImportRequest>) -> SyncImportResponse {
+    match data.0.ident {
+        TournamentIdentifier::Id(id) => {
+            SyncImportResponse::new(TOURNS_MAP.get().unwrap().get_mut(&id).map(|mut tourn| {
+                let author = tourn.get_state().author_for(&user.0.external_id);
+                tourn.check_import(&author, &data.0.sync)?;
+                Ok(tourn.import_sync(data.0.sync))
+            }))
+        }
+        TournamentIdentifier::Name(_name) => {
+            todo!("Yet to be impl-ed");
         }
     }
 }
 
 #[post("/manage/rollback", format = "json", data = "<data>")]
-pub fn rollback(data: Json<RollbackRequest>) -> RollbackResponse {
+pub fn rollback(user: AuthUser, data: Json<RollbackRequest>) -> RollbackResponse {
     match data.0.ident {
         TournamentIdentifier::Id(id) => {
-            todo!()
+            RollbackResponse::new(TOURNS_MAP.get().unwrap().get_mut(&id).map(|mut tourn| {
+                let author = tourn.get_state().author_for(&user.0.external_id);
+                tourn.check_rollback(&author, &data.0.sync)?;
+                Ok(tourn.apply_rollback(data.0.sync))
+            }))
         }
         TournamentIdentifier::Name(_name) => {
-            todo!()
+            todo!("Yet to be impl-ed");
         }
     }
 }
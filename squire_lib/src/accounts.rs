@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AccountId(pub Uuid);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AccountIdentifier {
+    Name(String),
+    Id(AccountId),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserAccount {
+    pub external_id: AccountId,
+    pub display_name: String,
+    pub account_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrgAccount {
+    pub external_id: AccountId,
+    pub display_name: String,
+    pub org_name: String,
+    pub owner: AccountId,
+    pub admins: Vec<AccountId>,
+}
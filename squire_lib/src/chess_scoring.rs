@@ -0,0 +1,564 @@
+pub use crate::{
+    player::PlayerId,
+    player_registry::PlayerRegistry,
+    round::Round,
+    round_registry::RoundRegistry,
+    scoring::{Score, Standings},
+    settings::{BuchholzScoringSetting, CumulativeScoringSetting, SonnebornBergerScoringSetting},
+};
+
+use crate::scoring::{seeded_draw, CountedRound, RoundCache};
+
+use serde::{Deserialize, Serialize};
+
+use std::{collections::HashMap, fmt::Display};
+
+/// A score from one of the chess-style scoring systems. Players are ranked by their points and
+/// then by the system's tiebreaker. Any remaining ties are broken by a random draw that is the
+/// same every time the standings are calculated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct ChessScore {
+    pub points: f64,
+    pub tiebreak: f64,
+    /// The player's place in the standings, starting from 1
+    pub rank: usize,
+}
+
+/// The points that a player gets for each kind of result. A chess game is usually worth 1 point
+/// for a win and half a point for a draw.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ChessPoints {
+    pub win: f64,
+    pub draw: f64,
+    pub loss: f64,
+    pub bye: f64,
+}
+
+/// Ranks players by the sum of their opponents' points. The median variant ignores the best and
+/// worst opponents.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BuchholzScoring {
+    points: ChessPoints,
+    median: bool,
+    /// The seed for the random draw that breaks ties that remain after the tiebreaker
+    #[serde(default)]
+    tiebreak_seed: u64,
+    /// This isn't serialized. Until it is rebuilt, the rounds are counted from scratch.
+    #[serde(skip)]
+    cache: Option<RoundCache>,
+}
+
+/// Ranks players by the sum of the points of the opponents that they beat and half the points of
+/// the opponents that they drew.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SonnebornBergerScoring {
+    points: ChessPoints,
+    #[serde(default)]
+    tiebreak_seed: u64,
+    /// This isn't serialized. Until it is rebuilt, the rounds are counted from scratch.
+    #[serde(skip)]
+    cache: Option<RoundCache>,
+}
+
+/// Ranks players by the sum of their running totals after each round, which favors players that
+/// won their early rounds.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CumulativeScoring {
+    points: ChessPoints,
+    #[serde(default)]
+    tiebreak_seed: u64,
+    /// This isn't serialized. Until it is rebuilt, the rounds are counted from scratch.
+    #[serde(skip)]
+    cache: Option<RoundCache>,
+}
+
+/// A player's result in a single round
+#[derive(Debug, Clone)]
+struct ChessResult {
+    opponents: Vec<PlayerId>,
+    earned: f64,
+}
+
+impl ChessPoints {
+    pub fn new() -> Self {
+        ChessPoints {
+            win: 1.0,
+            draw: 0.5,
+            loss: 0.0,
+            bye: 1.0,
+        }
+    }
+
+    /// Tallies the results of the counted rounds for all players, in the order that the rounds
+    /// were created. Dropped players are included as they count towards their opponents'
+    /// tiebreakers.
+    fn tally(
+        &self,
+        player_reg: &PlayerRegistry,
+        rounds: &RoundCache,
+    ) -> HashMap<PlayerId, Vec<ChessResult>> {
+        let mut digest: HashMap<PlayerId, Vec<ChessResult>> = player_reg
+            .players
+            .keys()
+            .map(|id| (id.clone(), Vec::new()))
+            .collect();
+        let mut rounds: Vec<&CountedRound> = rounds.rounds().collect();
+        rounds.sort_by_key(|r| r.match_number);
+        for round in rounds {
+            for plyr in &round.players {
+                let Some(results) = digest.get_mut(plyr) else {
+                    continue;
+                };
+                let earned = if round.is_bye {
+                    self.bye
+                } else {
+                    match &round.winner {
+                        Some(w) if w == plyr => self.win,
+                        Some(_) => self.loss,
+                        None => self.draw,
+                    }
+                };
+                let opponents = if round.is_bye {
+                    Vec::new()
+                } else {
+                    round
+                        .players
+                        .iter()
+                        .filter(|p| *p != plyr)
+                        .cloned()
+                        .collect()
+                };
+                results.push(ChessResult { opponents, earned });
+            }
+        }
+        digest
+    }
+
+    /// Tallies everyone's results and ranks them using the given tiebreaker. Remaining ties are
+    /// broken by a random draw with the given seed.
+    fn get_standings<F>(
+        &self,
+        player_reg: &PlayerRegistry,
+        rounds: &RoundCache,
+        seed: u64,
+        tiebreak: F,
+    ) -> Standings<ChessScore>
+    where
+        F: Fn(&[ChessResult], &HashMap<PlayerId, f64>) -> f64,
+    {
+        let tallies = self.tally(player_reg, rounds);
+        let points: HashMap<PlayerId, f64> = tallies
+            .iter()
+            .map(|(id, results)| (id.clone(), results.iter().map(|r| r.earned).sum()))
+            .collect();
+        let mut results: Vec<(PlayerId, ChessScore)> = tallies
+            .iter()
+            .filter(|(id, _)| {
+                player_reg
+                    .players
+                    .get(id)
+                    .map(|p| p.can_play())
+                    .unwrap_or_default()
+            })
+            .map(|(id, results)| {
+                let score = ChessScore {
+                    points: points[id],
+                    tiebreak: tiebreak(results, &points),
+                    rank: 0,
+                };
+                (id.clone(), score)
+            })
+            .collect();
+        results.sort_by(|(a_id, a), (b_id, b)| {
+            a.points
+                .total_cmp(&b.points)
+                .then(a.tiebreak.total_cmp(&b.tiebreak))
+                .then_with(|| seeded_draw(seed, a_id).cmp(&seeded_draw(seed, b_id)))
+        });
+        // Standings go from last place to first
+        let count = results.len();
+        for (i, (_, score)) in results.iter_mut().enumerate() {
+            score.rank = count - i;
+        }
+        Standings::new(results)
+    }
+}
+
+impl BuchholzScoring {
+    pub fn new() -> Self {
+        BuchholzScoring {
+            points: ChessPoints::new(),
+            median: false,
+            tiebreak_seed: 0,
+            cache: None,
+        }
+    }
+
+    pub fn update_setting(&mut self, setting: BuchholzScoringSetting) {
+        use BuchholzScoringSetting::*;
+        match setting {
+            WinPoints(p) => {
+                self.points.win = p;
+            }
+            DrawPoints(p) => {
+                self.points.draw = p;
+            }
+            LossPoints(p) => {
+                self.points.loss = p;
+            }
+            ByePoints(p) => {
+                self.points.bye = p;
+            }
+            Median(b) => {
+                self.median = b;
+            }
+            TiebreakSeed(seed) => {
+                self.tiebreak_seed = seed;
+            }
+        }
+    }
+
+    /// Updates the counted rounds after a round is certified, killed, or has its results changed
+    pub fn update_round(&mut self, round_reg: &RoundRegistry, round: &Round, first_match: u64) {
+        RoundCache::update(&mut self.cache, round_reg, round, first_match);
+    }
+
+    /// Checks the counted rounds against a full recount
+    pub fn verify_cache(&self, round_reg: &RoundRegistry, first_match: u64) -> bool {
+        RoundCache::verify(&self.cache, round_reg, first_match)
+    }
+
+    pub fn get_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        first_match: u64,
+    ) -> Standings<ChessScore> {
+        let rounds = RoundCache::get_or_tally(&self.cache, round_reg, first_match);
+        self.points.get_standings(
+            player_reg,
+            &rounds,
+            self.tiebreak_seed,
+            |results, points| {
+                let mut opps: Vec<f64> = results
+                    .iter()
+                    .flat_map(|r| r.opponents.iter())
+                    .filter_map(|o| points.get(o).copied())
+                    .collect();
+                if self.median && opps.len() > 2 {
+                    opps.sort_by(|a, b| a.total_cmp(b));
+                    opps.pop();
+                    opps.remove(0);
+                }
+                opps.iter().sum()
+            },
+        )
+    }
+}
+
+impl SonnebornBergerScoring {
+    pub fn new() -> Self {
+        SonnebornBergerScoring {
+            points: ChessPoints::new(),
+            tiebreak_seed: 0,
+            cache: None,
+        }
+    }
+
+    pub fn update_setting(&mut self, setting: SonnebornBergerScoringSetting) {
+        use SonnebornBergerScoringSetting::*;
+        match setting {
+            WinPoints(p) => {
+                self.points.win = p;
+            }
+            DrawPoints(p) => {
+                self.points.draw = p;
+            }
+            LossPoints(p) => {
+                self.points.loss = p;
+            }
+            ByePoints(p) => {
+                self.points.bye = p;
+            }
+            TiebreakSeed(seed) => {
+                self.tiebreak_seed = seed;
+            }
+        }
+    }
+
+    /// Updates the counted rounds after a round is certified, killed, or has its results changed
+    pub fn update_round(&mut self, round_reg: &RoundRegistry, round: &Round, first_match: u64) {
+        RoundCache::update(&mut self.cache, round_reg, round, first_match);
+    }
+
+    /// Checks the counted rounds against a full recount
+    pub fn verify_cache(&self, round_reg: &RoundRegistry, first_match: u64) -> bool {
+        RoundCache::verify(&self.cache, round_reg, first_match)
+    }
+
+    pub fn get_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        first_match: u64,
+    ) -> Standings<ChessScore> {
+        let win = self.points.win;
+        let rounds = RoundCache::get_or_tally(&self.cache, round_reg, first_match);
+        self.points.get_standings(
+            player_reg,
+            &rounds,
+            self.tiebreak_seed,
+            |results, points| {
+                if win == 0.0 {
+                    return 0.0;
+                }
+                results
+                    .iter()
+                    .flat_map(|r| {
+                        r.opponents
+                            .iter()
+                            .filter_map(|o| points.get(o).copied())
+                            .map(|p| p * r.earned / win)
+                    })
+                    .sum()
+            },
+        )
+    }
+}
+
+impl CumulativeScoring {
+    pub fn new() -> Self {
+        CumulativeScoring {
+            points: ChessPoints::new(),
+            tiebreak_seed: 0,
+            cache: None,
+        }
+    }
+
+    pub fn update_setting(&mut self, setting: CumulativeScoringSetting) {
+        use CumulativeScoringSetting::*;
+        match setting {
+            WinPoints(p) => {
+                self.points.win = p;
+            }
+            DrawPoints(p) => {
+                self.points.draw = p;
+            }
+            LossPoints(p) => {
+                self.points.loss = p;
+            }
+            ByePoints(p) => {
+                self.points.bye = p;
+            }
+            TiebreakSeed(seed) => {
+                self.tiebreak_seed = seed;
+            }
+        }
+    }
+
+    /// Updates the counted rounds after a round is certified, killed, or has its results changed
+    pub fn update_round(&mut self, round_reg: &RoundRegistry, round: &Round, first_match: u64) {
+        RoundCache::update(&mut self.cache, round_reg, round, first_match);
+    }
+
+    /// Checks the counted rounds against a full recount
+    pub fn verify_cache(&self, round_reg: &RoundRegistry, first_match: u64) -> bool {
+        RoundCache::verify(&self.cache, round_reg, first_match)
+    }
+
+    pub fn get_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        first_match: u64,
+    ) -> Standings<ChessScore> {
+        let rounds = RoundCache::get_or_tally(&self.cache, round_reg, first_match);
+        self.points
+            .get_standings(player_reg, &rounds, self.tiebreak_seed, |results, _| {
+                results
+                    .iter()
+                    .scan(0.0, |total, r| {
+                        *total += r.earned;
+                        Some(*total)
+                    })
+                    .sum()
+            })
+    }
+}
+
+impl Score for ChessScore {
+    fn primary_score(&self) -> f64 {
+        self.points
+    }
+}
+
+impl Display for ChessScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ChessScore {{ points: {}, tiebreak: {} }}",
+            self.points, self.tiebreak
+        )
+    }
+}
+
+impl Default for ChessPoints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for BuchholzScoring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for SonnebornBergerScoring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for CumulativeScoring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{operations::OpContext, player::Player, round::RoundResult};
+
+    /// Four players over three rounds:
+    /// 1. A beats B, C draws D
+    /// 2. A beats C, B beats D
+    /// 3. A draws D, B beats C
+    ///
+    /// This gives A 2.5 points, B 2, C 0.5, and D 1.
+    fn fixture() -> (PlayerRegistry, RoundRegistry, Vec<PlayerId>) {
+        let mut plyr_reg = PlayerRegistry::new();
+        let plyrs: Vec<PlayerId> = ["A", "B", "C", "D"]
+            .into_iter()
+            .map(|name| {
+                let plyr = Player::new(name.into());
+                let id = plyr.id.clone();
+                plyr_reg.import_player(plyr).unwrap();
+                id
+            })
+            .collect();
+        let mut rnd_reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        let rounds = [
+            (0, 1, Some(0)),
+            (2, 3, None),
+            (0, 2, Some(0)),
+            (1, 3, Some(1)),
+            (0, 3, None),
+            (1, 2, Some(1)),
+        ];
+        for (one, two, winner) in rounds {
+            let ident = rnd_reg.create_round(&mut ctx);
+            for p in [one, two] {
+                rnd_reg
+                    .add_player_to_round(&ident, plyrs[p].clone())
+                    .unwrap();
+            }
+            let result = match winner {
+                Some(w) => RoundResult::Wins(plyrs[w].clone(), 2),
+                None => RoundResult::Draw(),
+            };
+            rnd_reg
+                .get_mut_round(&ident)
+                .unwrap()
+                .force_certify(vec![result])
+                .unwrap();
+        }
+        (plyr_reg, rnd_reg, plyrs)
+    }
+
+    fn score<'a>(standings: &'a Standings<ChessScore>, plyr: &PlayerId) -> &'a ChessScore {
+        &standings
+            .scores
+            .iter()
+            .find(|(id, _)| id == plyr)
+            .unwrap()
+            .1
+    }
+
+    /// Gets the tiebreaks of the given players, in order
+    fn tiebreaks(standings: &Standings<ChessScore>, plyrs: &[PlayerId]) -> Vec<f64> {
+        plyrs.iter().map(|p| score(standings, p).tiebreak).collect()
+    }
+
+    #[test]
+    fn buchholz_sums_opponents_points() {
+        let (plyr_reg, rnd_reg, plyrs) = fixture();
+        let mut sys = BuchholzScoring::new();
+        let standings = sys.get_standings(&plyr_reg, &rnd_reg, 0);
+        let points: Vec<f64> = plyrs.iter().map(|p| score(&standings, p).points).collect();
+        assert_eq!(points, vec![2.5, 2.0, 0.5, 1.0]);
+        let ranks: Vec<usize> = plyrs.iter().map(|p| score(&standings, p).rank).collect();
+        assert_eq!(ranks, vec![1, 2, 4, 3]);
+        assert_eq!(tiebreaks(&standings, &plyrs), vec![3.5, 4.0, 5.5, 5.0]);
+        // The best and worst opponents are left out
+        sys.update_setting(BuchholzScoringSetting::Median(true));
+        let standings = sys.get_standings(&plyr_reg, &rnd_reg, 0);
+        assert_eq!(tiebreaks(&standings, &plyrs), vec![1.0, 1.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn sonneborn_berger_weighs_opponents_by_result() {
+        let (plyr_reg, rnd_reg, plyrs) = fixture();
+        let standings = SonnebornBergerScoring::new().get_standings(&plyr_reg, &rnd_reg, 0);
+        // A: 2 (B) + 0.5 (C) + 1 / 2 (D), D: 0.5 / 2 (C) + 2.5 / 2 (A)
+        assert_eq!(tiebreaks(&standings, &plyrs), vec![3.0, 1.5, 0.5, 1.5]);
+    }
+
+    #[test]
+    fn cumulative_sums_running_totals() {
+        let (plyr_reg, rnd_reg, plyrs) = fixture();
+        let standings = CumulativeScoring::new().get_standings(&plyr_reg, &rnd_reg, 0);
+        // A: 1 + 2 + 2.5, B: 0 + 1 + 2
+        assert_eq!(tiebreaks(&standings, &plyrs), vec![5.5, 3.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn byes_have_no_opponents() {
+        let (mut plyr_reg, mut rnd_reg, plyrs) = fixture();
+        let plyr = Player::new("E".into());
+        let id = plyr.id.clone();
+        plyr_reg.import_player(plyr).unwrap();
+        let ident = rnd_reg.create_round(&mut OpContext::new(Uuid::new_v4(), SystemTime::now()));
+        rnd_reg.add_player_to_round(&ident, id.clone()).unwrap();
+        rnd_reg.get_mut_round(&ident).unwrap().record_bye().unwrap();
+        let standings = BuchholzScoring::new().get_standings(&plyr_reg, &rnd_reg, 0);
+        let bye = score(&standings, &id);
+        assert_eq!((bye.points, bye.tiebreak), (1.0, 0.0));
+        assert_eq!(tiebreaks(&standings, &plyrs), vec![3.5, 4.0, 5.5, 5.0]);
+    }
+
+    #[test]
+    fn ties_are_broken_by_the_seeded_draw() {
+        let (plyr_reg, _, _) = fixture();
+        let rnd_reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let mut sys = CumulativeScoring::new();
+        let mut leaders = Vec::new();
+        for seed in 0..16 {
+            sys.update_setting(CumulativeScoringSetting::TiebreakSeed(seed));
+            let leader = sys.get_standings(&plyr_reg, &rnd_reg, 0).scores.pop();
+            assert_eq!(
+                leader,
+                sys.get_standings(&plyr_reg, &rnd_reg, 0).scores.pop()
+            );
+            leaders.push(leader.unwrap().0);
+        }
+        assert!(leaders.iter().any(|p| p != &leaders[0]));
+    }
+}
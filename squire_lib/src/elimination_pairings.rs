@@ -0,0 +1,662 @@
+pub use crate::{
+    error::TournamentError, player::PlayerId, player_registry::PlayerRegistry,
+    round_registry::RoundRegistry, settings::EliminationPairingsSetting,
+};
+use crate::{
+    pairings::Pairings,
+    player::PlayerStatus,
+    player_registry::PlayerIdentifier,
+    round::{Round, RoundResult, RoundStatus},
+    scoring::{Score, Standings},
+};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+/// A pairing system for single and double elimination brackets. The bracket is seeded from the
+/// standings when the first round is paired (1 vs 8, 4 vs 5, 2 vs 7, and 3 vs 6 for eight
+/// players). Winners are advanced as rounds are certified, and any match whose players are known
+/// can be paired without waiting for the rest of the bracket. A match that is certified as a draw
+/// is paired again. A player that has dropped forfeits their next match, so their opponent
+/// advances without it being played.
+///
+/// In a double elimination bracket, the player coming from the losers' bracket has to beat the
+/// undefeated player twice. If they win the grand final, the grand final is reset and played
+/// again. The reset can be turned off, in which case the grand final decides the bracket.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EliminationPairings {
+    double_elimination: bool,
+    #[serde(default)]
+    grand_final_reset: bool,
+    bracket: Option<Bracket>,
+}
+
+/// The section of the bracket that a match is in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BracketSection {
+    Winners,
+    Losers,
+    /// The grand final is at depth 0 and its reset, if there is one, is at depth 1
+    GrandFinal,
+}
+
+/// Where a player in a bracket match comes from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchSource {
+    /// The player with the given seed (starting from 0)
+    Seed(usize),
+    /// The winner of the bracket match at the given index
+    WinnerOf(usize),
+    /// The loser of the bracket match at the given index
+    LoserOf(usize),
+}
+
+/// The state of one of the spots in a bracket match
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum BracketSlot {
+    /// It is not yet known who will be in this spot
+    Pending,
+    /// No one will be in this spot, e.g. the bracket wasn't full
+    Empty,
+    Player(PlayerId),
+}
+
+/// A single match in a bracket
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BracketMatch {
+    pub section: BracketSection,
+    /// The round of the section that this match is in, starting from 0
+    pub depth: usize,
+    pub sources: [MatchSource; 2],
+    pub slots: [BracketSlot; 2],
+    pub winner: BracketSlot,
+    pub loser: BracketSlot,
+    /// The first match number that this match's round could have, set once it has been paired
+    pub(crate) paired_after: Option<u64>,
+}
+
+/// The structure of an elimination bracket, which can be rendered as is
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bracket {
+    pub seeds: Vec<PlayerId>,
+    pub matches: Vec<BracketMatch>,
+}
+
+impl EliminationPairings {
+    pub fn new(double_elimination: bool) -> Self {
+        EliminationPairings {
+            double_elimination,
+            grand_final_reset: true,
+            bracket: None,
+        }
+    }
+
+    pub fn is_double_elimination(&self) -> bool {
+        self.double_elimination
+    }
+
+    pub fn has_grand_final_reset(&self) -> bool {
+        self.grand_final_reset
+    }
+
+    /// Gets the bracket, if it has been seeded
+    pub fn bracket(&self) -> Option<&Bracket> {
+        self.bracket.as_ref()
+    }
+
+    /// Changes to the settings have no effect once the bracket has been seeded
+    pub fn update_setting(&mut self, setting: EliminationPairingsSetting) {
+        use EliminationPairingsSetting::*;
+        match setting {
+            DoubleElimination(b) => {
+                if self.bracket.is_none() {
+                    self.double_elimination = b;
+                }
+            }
+            GrandFinalReset(b) => {
+                if self.bracket.is_none() {
+                    self.grand_final_reset = b;
+                }
+            }
+        }
+    }
+
+    pub fn ready_to_pair(&self, plyr_reg: &PlayerRegistry, rnd_reg: &RoundRegistry) -> bool {
+        match &self.bracket {
+            None => true,
+            Some(bracket) => {
+                let mut bracket = bracket.clone();
+                bracket.advance(plyr_reg, rnd_reg);
+                let digest = bracket.pairable().next().is_some();
+                digest
+            }
+        }
+    }
+
+    /// Advances the winners of any certified rounds, and the opponents of any dropped players,
+    /// through the bracket
+    pub fn advance(&mut self, plyr_reg: &PlayerRegistry, rnd_reg: &RoundRegistry) {
+        if let Some(bracket) = self.bracket.as_mut() {
+            bracket.advance(plyr_reg, rnd_reg);
+        }
+    }
+
+    pub fn pair<S>(
+        &mut self,
+        players: &PlayerRegistry,
+        matches: &RoundRegistry,
+        mut standings: Standings<S>,
+    ) -> Option<Pairings>
+    where
+        S: Score,
+    {
+        if self.bracket.is_none() {
+            let seeds: Vec<PlayerId> = standings
+                .scores
+                .drain(0..)
+                .map(|(p, _)| p)
+                .filter(|p| {
+                    players.get_player_status(&PlayerIdentifier::Id(p.clone()))
+                        == Some(PlayerStatus::Registered)
+                })
+                .rev()
+                .collect();
+            if seeds.len() < 2 {
+                return None;
+            }
+            self.bracket = Some(Bracket::new(
+                seeds,
+                self.double_elimination,
+                self.grand_final_reset,
+            ));
+        }
+        let bracket = self.bracket.as_mut().unwrap();
+        bracket.advance(players, matches);
+        // Rounds are created in the same order as the pairings, starting from this match number
+        let next_match = matches.rounds.len() as u64;
+        let indices: Vec<usize> = bracket.pairable().collect();
+        if indices.is_empty() {
+            return None;
+        }
+        let mut digest = Pairings {
+            paired: Vec::with_capacity(indices.len()),
+            rejected: Vec::new(),
+        };
+        for i in indices {
+            let m = &mut bracket.matches[i];
+            m.paired_after = Some(next_match);
+            digest.paired.push(
+                m.slots
+                    .iter()
+                    .filter_map(|s| match s {
+                        BracketSlot::Player(p) => Some(p.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+            );
+        }
+        Some(digest)
+    }
+}
+
+impl Bracket {
+    fn new(seeds: Vec<PlayerId>, double_elimination: bool, grand_final_reset: bool) -> Self {
+        let size = seeds.len().next_power_of_two();
+        let mut matches = Vec::new();
+        // The first round of the winners' bracket
+        let order = seed_order(size);
+        let mut winners: Vec<usize> = Vec::with_capacity(size / 2);
+        for pair in order.chunks(2) {
+            winners.push(matches.len());
+            matches.push(BracketMatch::new(
+                BracketSection::Winners,
+                0,
+                [MatchSource::Seed(pair[0]), MatchSource::Seed(pair[1])],
+            ));
+        }
+        let mut winner_rounds = vec![winners];
+        while winner_rounds.last().unwrap().len() > 1 {
+            let prev = winner_rounds.last().unwrap().clone();
+            let depth = winner_rounds.len();
+            let round: Vec<usize> = prev
+                .chunks(2)
+                .map(|pair| {
+                    matches.push(BracketMatch::new(
+                        BracketSection::Winners,
+                        depth,
+                        [
+                            MatchSource::WinnerOf(pair[0]),
+                            MatchSource::WinnerOf(pair[1]),
+                        ],
+                    ));
+                    matches.len() - 1
+                })
+                .collect();
+            winner_rounds.push(round);
+        }
+        if double_elimination && size >= 4 {
+            // The losers of the first round play each other. After that, each round of the
+            // losers' bracket alternates between taking in the losers from the next round of the
+            // winners' bracket and halving the number of players that remain.
+            let mut depth = 0;
+            let mut losers: Vec<usize> = winner_rounds[0]
+                .chunks(2)
+                .map(|pair| {
+                    matches.push(BracketMatch::new(
+                        BracketSection::Losers,
+                        depth,
+                        [MatchSource::LoserOf(pair[0]), MatchSource::LoserOf(pair[1])],
+                    ));
+                    matches.len() - 1
+                })
+                .collect();
+            for wb_round in winner_rounds.iter().skip(1) {
+                depth += 1;
+                // The dropping players are fed in reverse order to avoid early rematches
+                losers = losers
+                    .iter()
+                    .zip(wb_round.iter().rev())
+                    .map(|(lb, wb)| {
+                        matches.push(BracketMatch::new(
+                            BracketSection::Losers,
+                            depth,
+                            [MatchSource::WinnerOf(*lb), MatchSource::LoserOf(*wb)],
+                        ));
+                        matches.len() - 1
+                    })
+                    .collect();
+                if losers.len() > 1 {
+                    depth += 1;
+                    losers = losers
+                        .chunks(2)
+                        .map(|pair| {
+                            matches.push(BracketMatch::new(
+                                BracketSection::Losers,
+                                depth,
+                                [
+                                    MatchSource::WinnerOf(pair[0]),
+                                    MatchSource::WinnerOf(pair[1]),
+                                ],
+                            ));
+                            matches.len() - 1
+                        })
+                        .collect();
+                }
+            }
+            let wb_final = winner_rounds.last().unwrap()[0];
+            matches.push(BracketMatch::new(
+                BracketSection::GrandFinal,
+                0,
+                [
+                    MatchSource::WinnerOf(wb_final),
+                    MatchSource::WinnerOf(losers[0]),
+                ],
+            ));
+            if grand_final_reset {
+                let final_match = matches.len() - 1;
+                matches.push(BracketMatch::new(
+                    BracketSection::GrandFinal,
+                    1,
+                    [
+                        MatchSource::WinnerOf(final_match),
+                        MatchSource::LoserOf(final_match),
+                    ],
+                ));
+            }
+        }
+        let mut digest = Bracket { seeds, matches };
+        digest.resolve(&HashSet::new());
+        digest
+    }
+
+    /// Gets the player that won the bracket, if the bracket is finished
+    pub fn champion(&self) -> Option<&PlayerId> {
+        match &self.matches.last()?.winner {
+            BracketSlot::Player(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if every match in the bracket has been decided
+    pub fn is_finished(&self) -> bool {
+        self.matches
+            .iter()
+            .all(|m| m.winner != BracketSlot::Pending)
+    }
+
+    /// The indices of the matches that are ready to be paired
+    fn pairable(&self) -> impl Iterator<Item = usize> + '_ {
+        self.matches.iter().enumerate().filter_map(|(i, m)| {
+            (m.winner == BracketSlot::Pending
+                && m.paired_after.is_none()
+                && m.slots.iter().all(|s| matches!(s, BracketSlot::Player(_))))
+            .then_some(i)
+        })
+    }
+
+    /// Records the results of any certified rounds and moves players through the bracket
+    fn advance(&mut self, plyr_reg: &PlayerRegistry, rnd_reg: &RoundRegistry) {
+        let dropped: HashSet<PlayerId> = self
+            .seeds
+            .iter()
+            .filter(|p| {
+                plyr_reg.get_player_status(&PlayerIdentifier::Id((*p).clone()))
+                    != Some(PlayerStatus::Registered)
+            })
+            .cloned()
+            .collect();
+        let mut by_players: HashMap<(PlayerId, PlayerId), Vec<&Round>> = HashMap::new();
+        for rnd in rnd_reg.rounds.values() {
+            if rnd.players.len() == 2 && rnd.status != RoundStatus::Dead {
+                by_players
+                    .entry((rnd.players[0].clone(), rnd.players[1].clone()))
+                    .or_default()
+                    .push(rnd);
+            }
+        }
+        for m in self.matches.iter_mut() {
+            let (after, one, two) = match (&m.paired_after, &m.slots) {
+                (Some(after), [BracketSlot::Player(one), BracketSlot::Player(two)])
+                    if m.winner == BracketSlot::Pending =>
+                {
+                    (*after, one.clone(), two.clone())
+                }
+                _ => continue,
+            };
+            let rnd = [(one.clone(), two.clone()), (two.clone(), one.clone())]
+                .iter()
+                .filter_map(|key| by_players.get(key))
+                .flatten()
+                .filter(|r| r.match_number >= after)
+                .min_by_key(|r| r.match_number);
+            match rnd {
+                // The round was removed, so the match needs to be paired again
+                None => {
+                    m.paired_after = None;
+                }
+                Some(rnd) if rnd.is_certified() => match round_winner(rnd) {
+                    Some(winner) => {
+                        let loser = if winner == one { two } else { one };
+                        m.winner = BracketSlot::Player(winner);
+                        m.loser = BracketSlot::Player(loser);
+                    }
+                    // Someone has to advance, so a drawn match is paired and played again
+                    None => {
+                        m.paired_after = None;
+                    }
+                },
+                Some(_) => {}
+            }
+        }
+        self.resolve(&dropped);
+    }
+
+    /// Fills in the slots of matches whose sources have been decided. Matches with an empty slot or
+    /// a dropped player that haven't been paired are decided without being played.
+    fn resolve(&mut self, dropped: &HashSet<PlayerId>) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in 0..self.matches.len() {
+                for s in 0..2 {
+                    if self.matches[i].slots[s] != BracketSlot::Pending {
+                        continue;
+                    }
+                    let slot = match self.matches[i].sources[s] {
+                        MatchSource::Seed(n) => match self.seeds.get(n) {
+                            Some(p) => BracketSlot::Player(p.clone()),
+                            None => BracketSlot::Empty,
+                        },
+                        MatchSource::WinnerOf(n) => self.matches[n].winner.clone(),
+                        MatchSource::LoserOf(n) => self.matches[n].loser.clone(),
+                    };
+                    if slot != BracketSlot::Pending {
+                        self.matches[i].slots[s] = slot;
+                        changed = true;
+                    }
+                }
+                if let Some((winner, loser)) = self.skipped_reset(i) {
+                    let m = &mut self.matches[i];
+                    m.winner = winner;
+                    m.loser = loser;
+                    changed = true;
+                }
+                let m = &mut self.matches[i];
+                if m.winner != BracketSlot::Pending {
+                    continue;
+                }
+                let walkover = match &m.slots {
+                    [BracketSlot::Empty, other] | [other, BracketSlot::Empty]
+                        if *other != BracketSlot::Pending =>
+                    {
+                        Some(other.clone())
+                    }
+                    [BracketSlot::Player(one), BracketSlot::Player(two)]
+                        if m.paired_after.is_none() =>
+                    {
+                        match (dropped.contains(one), dropped.contains(two)) {
+                            (false, false) => None,
+                            (false, true) => Some(BracketSlot::Player(one.clone())),
+                            (true, false) => Some(BracketSlot::Player(two.clone())),
+                            (true, true) => Some(BracketSlot::Empty),
+                        }
+                    }
+                    _ => None,
+                };
+                if let Some(winner) = walkover {
+                    m.winner = winner;
+                    m.loser = BracketSlot::Empty;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// The reset of the grand final is only played if the player from the losers' bracket won the
+    /// grand final. Otherwise, the grand final's result stands. Returns the winner and loser of
+    /// the given match if it is a reset that isn't needed.
+    fn skipped_reset(&self, i: usize) -> Option<(BracketSlot, BracketSlot)> {
+        let m = &self.matches[i];
+        if m.section != BracketSection::GrandFinal
+            || m.depth == 0
+            || m.winner != BracketSlot::Pending
+        {
+            return None;
+        }
+        let MatchSource::WinnerOf(gf) = m.sources[0] else {
+            return None;
+        };
+        let gf = &self.matches[gf];
+        (gf.winner != BracketSlot::Pending && gf.winner == gf.slots[0])
+            .then(|| (gf.winner.clone(), gf.loser.clone()))
+    }
+}
+
+impl BracketMatch {
+    fn new(section: BracketSection, depth: usize, sources: [MatchSource; 2]) -> Self {
+        BracketMatch {
+            section,
+            depth,
+            sources,
+            slots: [BracketSlot::Pending, BracketSlot::Pending],
+            winner: BracketSlot::Pending,
+            loser: BracketSlot::Pending,
+            paired_after: None,
+        }
+    }
+}
+
+/// Gets the seeds (starting from 0) in the order that they are placed in the first round of a
+/// bracket of the given size, e.g. [0, 7, 3, 4, 1, 6, 2, 5] for a bracket of 8 players. This
+/// keeps the top seeds apart for as long as possible.
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut digest = vec![0];
+    while digest.len() < size {
+        let len = digest.len() * 2;
+        digest = digest.into_iter().flat_map(|s| [s, len - 1 - s]).collect();
+    }
+    digest
+}
+
+/// Determines the winner of a round. If no winner was recorded, it is worked out from the
+/// round's results.
+fn round_winner(rnd: &Round) -> Option<PlayerId> {
+    rnd.winner.clone().or_else(|| rnd.match_winner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{operations::OpContext, player::Player};
+
+    /// Plays out the bracket. The closure picks which slot wins each match.
+    fn play<F>(bracket: &mut Bracket, plyrs: &PlayerRegistry, pick: F) -> RoundRegistry
+    where
+        F: Fn(&BracketMatch) -> usize,
+    {
+        let mut reg = RoundRegistry::new(0, Duration::from_secs(0));
+        while play_round(bracket, plyrs, &mut reg, &pick) {}
+        reg
+    }
+
+    /// Plays every match that can be paired. Returns `false` if there were none.
+    fn play_round<F>(
+        bracket: &mut Bracket,
+        plyrs: &PlayerRegistry,
+        reg: &mut RoundRegistry,
+        pick: F,
+    ) -> bool
+    where
+        F: Fn(&BracketMatch) -> usize,
+    {
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        bracket.advance(plyrs, reg);
+        let indices: Vec<usize> = bracket.pairable().collect();
+        if indices.is_empty() {
+            return false;
+        }
+        for i in indices {
+            let m = &mut bracket.matches[i];
+            m.paired_after = Some(reg.rounds.len() as u64);
+            let plyrs: Vec<PlayerId> = m
+                .slots
+                .iter()
+                .filter_map(|s| match s {
+                    BracketSlot::Player(p) => Some(p.clone()),
+                    _ => None,
+                })
+                .collect();
+            let winner = plyrs[pick(m)].clone();
+            let ident = reg.create_round(&mut ctx);
+            for p in plyrs {
+                reg.add_player_to_round(&ident, p).unwrap();
+            }
+            reg.get_mut_round(&ident)
+                .unwrap()
+                .force_certify(vec![RoundResult::Wins(winner, 2)])
+                .unwrap();
+        }
+        true
+    }
+
+    fn seeds(count: usize) -> (PlayerRegistry, Vec<PlayerId>) {
+        let mut plyrs = PlayerRegistry::new();
+        let seeds = (0..count)
+            .map(|i| {
+                let plyr = Player::new(format!("Player {i}"));
+                let id = plyr.id.clone();
+                plyrs.import_player(plyr).unwrap();
+                id
+            })
+            .collect();
+        (plyrs, seeds)
+    }
+
+    fn is_grand_final(m: &BracketMatch) -> bool {
+        m.section == BracketSection::GrandFinal && m.depth == 0
+    }
+
+    #[test]
+    fn reset_is_skipped_if_the_undefeated_player_wins() {
+        let (plyrs, seeds) = seeds(4);
+        let mut bracket = Bracket::new(seeds.clone(), true, true);
+        assert_eq!(bracket.matches.len(), 7);
+        let reg = play(&mut bracket, &plyrs, |_| 0);
+        assert!(bracket.is_finished());
+        assert_eq!(bracket.champion(), Some(&seeds[0]));
+        assert_eq!(reg.rounds.len(), 6);
+    }
+
+    #[test]
+    fn reset_is_played_if_the_losers_bracket_player_wins() {
+        let (plyrs, seeds) = seeds(4);
+        let mut bracket = Bracket::new(seeds.clone(), true, true);
+        // The losers' bracket player wins the grand final, but then loses the reset, where they
+        // are in the first slot
+        let reg = play(&mut bracket, &plyrs, |m| {
+            if m.section == BracketSection::GrandFinal {
+                1
+            } else {
+                0
+            }
+        });
+        assert!(bracket.is_finished());
+        assert_eq!(reg.rounds.len(), 7);
+        assert_eq!(bracket.champion(), Some(&seeds[0]));
+    }
+
+    #[test]
+    fn grand_final_decides_without_a_reset() {
+        let (plyrs, seeds) = seeds(4);
+        let mut bracket = Bracket::new(seeds.clone(), true, false);
+        assert_eq!(bracket.matches.len(), 6);
+        let reg = play(
+            &mut bracket,
+            &plyrs,
+            |m| if is_grand_final(m) { 1 } else { 0 },
+        );
+        assert!(bracket.is_finished());
+        assert_eq!(reg.rounds.len(), 6);
+        assert_ne!(bracket.champion(), Some(&seeds[0]));
+    }
+
+    #[test]
+    fn dropped_players_forfeit_their_next_match() {
+        let (mut plyrs, seeds) = seeds(4);
+        let mut bracket = Bracket::new(seeds.clone(), false, false);
+        let mut reg = RoundRegistry::new(0, Duration::from_secs(0));
+        // The first and second seeds win their first matches, then the second seed drops
+        assert!(play_round(&mut bracket, &plyrs, &mut reg, |_| 0));
+        plyrs.drop_player(&PlayerIdentifier::Id(seeds[1].clone()));
+        assert!(!play_round(&mut bracket, &plyrs, &mut reg, |_| 0));
+        assert_eq!(reg.rounds.len(), 2);
+        assert!(bracket.is_finished());
+        assert_eq!(bracket.champion(), Some(&seeds[0]));
+    }
+
+    #[test]
+    fn dropped_players_are_not_paired_in_the_losers_bracket() {
+        let (mut plyrs, seeds) = seeds(4);
+        let mut bracket = Bracket::new(seeds.clone(), true, true);
+        let mut reg = RoundRegistry::new(0, Duration::from_secs(0));
+        assert!(play_round(&mut bracket, &plyrs, &mut reg, |_| 0));
+        plyrs.drop_player(&PlayerIdentifier::Id(seeds[3].clone()));
+        while play_round(&mut bracket, &plyrs, &mut reg, |_| 0) {}
+        assert!(bracket.is_finished());
+        assert_eq!(bracket.champion(), Some(&seeds[0]));
+        // The losers' bracket match against the fourth seed is skipped
+        assert_eq!(reg.rounds.len(), 5);
+        assert_eq!(
+            reg.rounds
+                .values()
+                .filter(|r| r.players.contains(&seeds[3]))
+                .count(),
+            1
+        );
+    }
+}
@@ -23,6 +23,17 @@ pub enum TournamentError {
     PlayerNotCheckedIn,
     IncompatiblePairingSystem,
     IncompatibleScoringSystem,
+    InsufficientPermissions,
+    NoNextPhase,
+    IncorrectTeamSize,
+    ClockPaused,
+    ClockRunning,
+    TimeCalled,
+    TimeNotCalled,
+    NoExtraTurns,
+    InvalidResult,
+    NoResult,
+    RoundDisputed,
 }
 
 impl fmt::Display for TournamentError {
@@ -41,6 +52,17 @@ impl fmt::Display for TournamentError {
             PlayerNotCheckedIn => "PlayerNotCheckedIn",
             IncompatibleScoringSystem => "IncompatibleScoringSystem",
             IncompatiblePairingSystem => "IncompatiblePairingSystem",
+            InsufficientPermissions => "InsufficientPermissions",
+            NoNextPhase => "NoNextPhase",
+            IncorrectTeamSize => "IncorrectTeamSize",
+            ClockPaused => "ClockPaused",
+            ClockRunning => "ClockRunning",
+            TimeCalled => "TimeCalled",
+            TimeNotCalled => "TimeNotCalled",
+            NoExtraTurns => "NoExtraTurns",
+            InvalidResult => "InvalidResult",
+            NoResult => "NoResult",
+            RoundDisputed => "RoundDisputed",
         };
         write!(f, "{}", s)
     }
@@ -1,6 +1,6 @@
 use crate::tournament::pairing_system_factory;
 use crate::tournament::scoring_system_factory;
-use crate::tournament::PairingSystem::{Fluid, Swiss};
+use crate::tournament::PairingSystem::{Elimination, Fluid, RoundRobin, Swiss};
 use crate::tournament::{Tournament, TournamentId, TournamentPreset, TournamentStatus};
 use crate::{
     error::TournamentError,
@@ -22,6 +22,7 @@ use crate::{
 use dashmap::DashMap;
 use once_cell::sync::OnceCell;
 use serde_json;
+use std::collections::HashMap;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::fs::{read_to_string, remove_file, rename, write};
@@ -176,6 +177,15 @@ impl TournamentId {
             Fluid(_) => {
                 return TournamentPreset::Fluid as i32;
             }
+            RoundRobin(_) => {
+                return TournamentPreset::RoundRobin as i32;
+            }
+            Elimination(sys) => {
+                if sys.is_double_elimination() {
+                    return TournamentPreset::DoubleElimination as i32;
+                }
+                return TournamentPreset::SingleElimination as i32;
+            }
         }
     }
 
@@ -338,16 +348,26 @@ pub extern "C" fn new_tournament_from_settings(
         use_table_number: use_table_number,
         format: String::from(unsafe { CStr::from_ptr(__format).to_str().unwrap().to_string() }),
         game_size: game_size,
+        team_size: 1,
+        extra_turns: 5,
         min_deck_count: min_deck_count,
         max_deck_count: max_deck_count,
         player_reg: PlayerRegistry::new(),
         round_reg: RoundRegistry::new(0, Duration::from_secs(3000)),
         pairing_sys: pairing_system_factory(&preset, 2),
         scoring_sys: scoring_system_factory(&preset),
+        phases: Vec::new(),
+        completed_phases: Vec::new(),
+        seeding: Vec::new(),
+        phase_start: 0,
+        pending_pairings: Vec::new(),
+        standings_history: Vec::new(),
         reg_open: reg_open,
         require_check_in: require_check_in,
         require_deck_reg: require_deck_reg,
         status: TournamentStatus::Planned,
+        judges: HashMap::new(),
+        admins: HashMap::new(),
     };
     let tid: TournamentId = tournament.id;
 
@@ -1,6 +1,6 @@
 use crate::{
     error::TournamentError,
-    pairings::Pairings,
+    pairings::{balance_seats, pod_sizes, Pairings},
     player::PlayerId,
     player_registry::PlayerRegistry,
     round_registry::RoundRegistry,
@@ -17,6 +17,8 @@ pub struct FluidPairings {
     players_per_match: u8,
     check_ins: HashSet<PlayerId>,
     queue: Vec<PlayerId>,
+    #[serde(default)]
+    min_players_per_match: Option<u8>,
 }
 
 impl FluidPairings {
@@ -25,6 +27,7 @@ impl FluidPairings {
             players_per_match,
             check_ins: HashSet::new(),
             queue: Vec::new(),
+            min_players_per_match: None,
         }
     }
 
@@ -42,16 +45,28 @@ impl FluidPairings {
             MatchSize(s) => {
                 self.players_per_match = s;
             }
+            MinMatchSize(s) => {
+                self.min_players_per_match = Some(s);
+            }
         }
     }
 
+    fn min_match_size(&self) -> u8 {
+        self.min_players_per_match.unwrap_or(self.players_per_match)
+    }
+
     pub fn ready_to_pair(&self) -> bool {
-        self.check_ins.len() + self.queue.len() >= self.players_per_match as usize
+        !pod_sizes(
+            self.check_ins.len() + self.queue.len(),
+            self.min_match_size(),
+            self.players_per_match,
+        )
+        .is_empty()
     }
 
     fn valid_pairing(&self, matches: &RoundRegistry, known: &[&PlayerId], new: &PlayerId) -> bool {
         if let Some(opps) = matches.opponents.get(new) {
-            known.iter().any(|p| !opps.contains(p))
+            known.iter().all(|p| !opps.contains(p))
         } else {
             true
         }
@@ -62,36 +77,42 @@ impl FluidPairings {
             return None;
         }
         let mut plyrs: Vec<PlayerId> = self.check_ins.drain().collect();
+        // Sets have no order, so this keeps the pairings the same when the tournament is replayed
+        plyrs.sort();
         plyrs.extend(self.queue.drain(0..));
         let mut digest = Pairings {
             paired: Vec::with_capacity(plyrs.len() / self.players_per_match as usize + 1),
             rejected: Vec::new(),
         };
-        while plyrs.len() > self.players_per_match as usize {
-            let mut index_buffer: Vec<usize> = Vec::with_capacity(self.players_per_match as usize);
-            let mut id_buffer: Vec<&PlayerId> = Vec::with_capacity(self.players_per_match as usize);
+        while let Some(size) = pod_sizes(plyrs.len(), self.min_match_size(), self.players_per_match)
+            .first()
+            .copied()
+        {
+            let mut index_buffer: Vec<usize> = Vec::with_capacity(size);
+            let mut id_buffer: Vec<&PlayerId> = Vec::with_capacity(size);
             index_buffer.push(0);
             id_buffer.push(&plyrs[0]);
             for (i, _) in plyrs.iter().enumerate().skip(1) {
                 if self.valid_pairing(matches, &id_buffer, &plyrs[i]) {
                     index_buffer.push(i);
                     id_buffer.push(&plyrs[i]);
-                    if index_buffer.len() == self.players_per_match as usize {
+                    if index_buffer.len() == size {
                         break;
                     }
                 }
             }
-            if index_buffer.len() == self.players_per_match as usize {
-                let mut pairing: Vec<PlayerId> =
-                    Vec::with_capacity(self.players_per_match as usize);
-                for i in index_buffer {
-                    pairing.push(plyrs[i].clone());
+            if index_buffer.len() == size {
+                let pairing: Vec<PlayerId> =
+                    index_buffer.iter().map(|i| plyrs[*i].clone()).collect();
+                for i in index_buffer.into_iter().rev() {
+                    plyrs.remove(i);
                 }
-                digest.paired.push(pairing);
+                digest.paired.push(balance_seats(pairing, matches));
             } else {
                 self.queue.push(plyrs.pop().unwrap());
             }
         }
+        self.queue.extend(plyrs.drain(0..).rev());
         Some(digest)
     }
 }
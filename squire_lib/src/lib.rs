@@ -13,19 +13,27 @@ extern crate lazy_static;
 
 //#![cfg_attr(feature = "ffi", deny(improper_ctypes_definitions))]
 //#![deny(improper_ctypes_definitions)]
+pub mod accounts;
+pub mod chess_scoring;
+pub mod elimination_pairings;
 pub mod error;
 #[cfg(feature = "ffi")]
 pub mod ffi;
 pub mod fluid_pairings;
+pub mod match_slips;
+pub(crate) mod matching;
 pub mod operations;
 pub mod pairings;
+pub mod placement_scoring;
 pub mod player;
 pub mod player_registry;
 pub mod round;
 pub mod round_registry;
+pub mod round_robin_pairings;
 pub mod scoring;
 pub mod settings;
 pub mod standard_scoring;
 pub mod swiss_pairings;
+pub mod team;
 pub mod tournament;
 pub mod tournament_manager;
@@ -0,0 +1,368 @@
+use std::{fmt::Write, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::TournamentError, player_registry::PlayerIdentifier, round_registry::RoundIdentifier,
+    tournament::Tournament,
+};
+
+/// The formats that pairings and match slips can be rendered in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum SlipFormat {
+    Text,
+    Html,
+    Csv,
+}
+
+/// A match as it appears on posted pairings and result slips
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SlipMatch {
+    pub match_number: u64,
+    pub table_number: u64,
+    /// The names of the players, in seat order
+    pub players: Vec<String>,
+    /// The length of the match, including any time extensions
+    pub time_limit: Duration,
+    pub is_bye: bool,
+}
+
+/// The pairings for a set of rounds, e.g. the rounds created by a `PairRound` operation. These
+/// can be rendered as pairings sorted by name, pairings sorted by table, or a result slip for
+/// each match.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MatchSlips {
+    /// The matches, sorted by table number
+    pub matches: Vec<SlipMatch>,
+}
+
+/// A titled table of text that can be rendered in any format
+struct Sheet {
+    title: Option<String>,
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl MatchSlips {
+    /// Collects the given rounds from the tournament
+    pub fn new(tourn: &Tournament, rounds: &[RoundIdentifier]) -> Result<Self, TournamentError> {
+        let mut matches = rounds
+            .iter()
+            .map(|ident| {
+                let rnd = tourn
+                    .round_reg
+                    .get_round(ident)
+                    .ok_or(TournamentError::RoundLookup)?;
+                let players = rnd
+                    .players
+                    .iter()
+                    .map(|id| {
+                        tourn
+                            .player_reg
+                            .get_player(&PlayerIdentifier::Id(id.clone()))
+                            .map(|p| p.name.clone())
+                            .ok_or(TournamentError::PlayerLookup)
+                    })
+                    .collect::<Result<Vec<String>, TournamentError>>()?;
+                Ok(SlipMatch {
+                    match_number: rnd.match_number,
+                    table_number: rnd.table_number,
+                    players,
+                    time_limit: rnd.length + rnd.extension,
+                    is_bye: rnd.is_bye,
+                })
+            })
+            .collect::<Result<Vec<SlipMatch>, TournamentError>>()?;
+        matches.sort_by_key(|m| (m.is_bye, m.table_number, m.match_number));
+        Ok(MatchSlips { matches })
+    }
+
+    /// Lists every player alphabetically along with their table and opponents
+    pub fn pairings_by_name(&self, format: SlipFormat) -> String {
+        let mut rows: Vec<Vec<String>> = self
+            .matches
+            .iter()
+            .flat_map(|m| {
+                m.players.iter().map(move |name| {
+                    let opponents = if m.is_bye {
+                        "BYE".to_owned()
+                    } else {
+                        m.players
+                            .iter()
+                            .filter(|p| *p != name)
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    vec![
+                        name.clone(),
+                        table_label(m),
+                        opponents,
+                        format_time_limit(m.time_limit),
+                    ]
+                })
+            })
+            .collect();
+        rows.sort_by_cached_key(|row| row[0].to_lowercase());
+        Sheet {
+            title: Some("Pairings by Name".to_owned()),
+            headers: vec!["Player", "Table", "Opponents", "Time Limit"],
+            rows,
+        }
+        .render(format)
+    }
+
+    /// Lists every match in table order. Byes are listed last.
+    pub fn pairings_by_table(&self, format: SlipFormat) -> String {
+        let rows = self
+            .matches
+            .iter()
+            .map(|m| {
+                let players = if m.is_bye {
+                    format!("{} (BYE)", m.players.join(", "))
+                } else {
+                    m.players.join(" vs. ")
+                };
+                vec![
+                    table_label(m),
+                    m.match_number.to_string(),
+                    players,
+                    format_time_limit(m.time_limit),
+                ]
+            })
+            .collect();
+        Sheet {
+            title: Some("Pairings by Table".to_owned()),
+            headers: vec!["Table", "Match", "Players", "Time Limit"],
+            rows,
+        }
+        .render(format)
+    }
+
+    /// Creates a slip for each match that the players fill out and sign once they are done.
+    /// Byes don't get a slip. As a CSV, every slip is put into one sheet with a row per player.
+    pub fn result_slips(&self, format: SlipFormat) -> String {
+        let matches = self.matches.iter().filter(|m| !m.is_bye);
+        if format == SlipFormat::Csv {
+            let rows = matches
+                .flat_map(|m| {
+                    m.players.iter().map(|name| {
+                        vec![
+                            m.match_number.to_string(),
+                            m.table_number.to_string(),
+                            format_time_limit(m.time_limit),
+                            name.clone(),
+                            String::new(),
+                            String::new(),
+                            String::new(),
+                        ]
+                    })
+                })
+                .collect();
+            return Sheet {
+                title: None,
+                headers: vec![
+                    "Match",
+                    "Table",
+                    "Time Limit",
+                    "Player",
+                    "Game Wins",
+                    "Draws",
+                    "Signature",
+                ],
+                rows,
+            }
+            .render(format);
+        }
+        let slips: Vec<String> = matches
+            .map(|m| {
+                Sheet {
+                    title: Some(format!(
+                        "Match {} - Table {} - Time Limit: {}",
+                        m.match_number,
+                        m.table_number,
+                        format_time_limit(m.time_limit)
+                    )),
+                    headers: vec!["Player", "Game Wins", "Draws", "Signature"],
+                    rows: m
+                        .players
+                        .iter()
+                        .map(|name| {
+                            vec![
+                                name.clone(),
+                                "____".to_owned(),
+                                "____".to_owned(),
+                                "____________________".to_owned(),
+                            ]
+                        })
+                        .collect(),
+                }
+                .render(format)
+            })
+            .collect();
+        match format {
+            // Leaves a line between slips to cut along
+            SlipFormat::Text => slips.join(&format!("{}\n", "-".repeat(60))),
+            SlipFormat::Html | SlipFormat::Csv => slips.concat(),
+        }
+    }
+}
+
+impl Sheet {
+    fn render(&self, format: SlipFormat) -> String {
+        match format {
+            SlipFormat::Text => self.render_text(),
+            SlipFormat::Html => self.render_html(),
+            SlipFormat::Csv => self.render_csv(),
+        }
+    }
+
+    /// Lines up the columns so that the sheet can be printed in a fixed-width font
+    fn render_text(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let line = |cells: Vec<&str>| {
+            let mut digest = String::new();
+            for (cell, width) in cells.into_iter().zip(&widths) {
+                let _ = write!(digest, "{cell:width$}  ");
+            }
+            digest.trim_end().to_owned() + "\n"
+        };
+        let mut digest = String::new();
+        if let Some(title) = &self.title {
+            digest += title;
+            digest += "\n\n";
+        }
+        digest += &line(self.headers.clone());
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        digest += &line(rule.iter().map(String::as_str).collect());
+        for row in &self.rows {
+            digest += &line(row.iter().map(String::as_str).collect());
+        }
+        digest
+    }
+
+    fn render_html(&self) -> String {
+        let mut digest = String::new();
+        if let Some(title) = &self.title {
+            let _ = writeln!(digest, "<h2>{}</h2>", escape_html(title));
+        }
+        digest += "<table>\n<thead>\n<tr>";
+        for header in &self.headers {
+            let _ = write!(digest, "<th>{}</th>", escape_html(header));
+        }
+        digest += "</tr>\n</thead>\n<tbody>\n";
+        for row in &self.rows {
+            digest += "<tr>";
+            for cell in row {
+                let _ = write!(digest, "<td>{}</td>", escape_html(cell));
+            }
+            digest += "</tr>\n";
+        }
+        digest += "</tbody>\n</table>\n";
+        digest
+    }
+
+    /// The title is left out so that the output can be loaded straight into a spreadsheet
+    fn render_csv(&self) -> String {
+        let mut digest = String::new();
+        let headers: Vec<String> = self.headers.iter().map(|h| escape_csv(h)).collect();
+        digest += &headers.join(",");
+        digest += "\n";
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(|c| escape_csv(c)).collect();
+            digest += &cells.join(",");
+            digest += "\n";
+        }
+        digest
+    }
+}
+
+fn table_label(m: &SlipMatch) -> String {
+    if m.is_bye {
+        "-".to_owned()
+    } else {
+        m.table_number.to_string()
+    }
+}
+
+fn format_time_limit(len: Duration) -> String {
+    let secs = len.as_secs();
+    match secs % 60 {
+        0 => format!("{} min", secs / 60),
+        s => format!("{} min {s} sec", secs / 60),
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut digest = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => digest += "&amp;",
+            '<' => digest += "&lt;",
+            '>' => digest += "&gt;",
+            '"' => digest += "&quot;",
+            '\'' => digest += "&#39;",
+            c => digest.push(c),
+        }
+    }
+    digest
+}
+
+/// Quotes a field if it contains anything that would break up the row
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slips(names: &[&str]) -> MatchSlips {
+        MatchSlips {
+            matches: vec![SlipMatch {
+                match_number: 1,
+                table_number: 4,
+                players: names.iter().map(|n| (*n).to_owned()).collect(),
+                time_limit: Duration::from_secs(3000),
+                is_bye: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        assert_eq!(
+            escape_html(r#"<b>"Tom" & 'Jerry'</b>"#),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+        assert_eq!(escape_html("Alice"), "Alice");
+        let html = slips(&["<script>alert(1)</script>", "Bob & Co"]).result_slips(SlipFormat::Html);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<td>&lt;script&gt;alert(1)&lt;/script&gt;</td>"));
+        assert!(html.contains("<td>Bob &amp; Co</td>"));
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(escape_csv("Alice"), "Alice");
+        assert_eq!(escape_csv("Smith, John"), r#""Smith, John""#);
+        assert_eq!(escape_csv(r#"The "Pro""#), r#""The ""Pro""""#);
+        assert_eq!(escape_csv("two\nlines"), "\"two\nlines\"");
+        assert_eq!(escape_csv("cr\r"), "\"cr\r\"");
+        let csv = slips(&["Smith, John", r#"The "Pro""#]).pairings_by_table(SlipFormat::Csv);
+        assert_eq!(
+            csv,
+            "Table,Match,Players,Time Limit\n4,1,\"Smith, John vs. The \"\"Pro\"\"\",50 min\n"
+        );
+    }
+}
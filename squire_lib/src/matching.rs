@@ -0,0 +1,823 @@
+//! A maximum weight matching algorithm for general graphs.
+//!
+//! This is a port of Joris van Rantwijk's implementation of Edmonds' blossom algorithm (as
+//! described by Galil in "Efficient Algorithms for Finding Maximum Matching in Graphs"), which
+//! runs in O(n^3) time. It is used to find optimal pairings for two-player games.
+//!
+//! Vertices are represented by their index. Throughout the algorithm, `-1` is used as a null
+//! value in place of `Option`s to keep the bookkeeping close to the original algorithm.
+
+/// An undirected, weighted edge between two vertices
+pub(crate) type Edge = (usize, usize, i64);
+
+/// Computes a maximum weight matching of the graph given by the edges. If `max_cardinality` is
+/// `true`, only matchings with the largest number of edges are considered.
+///
+/// The returned vector has an entry for every vertex, which contains the vertex it is matched
+/// with (if any).
+pub(crate) fn max_weight_matching(edges: &[Edge], max_cardinality: bool) -> Vec<Option<usize>> {
+    if edges.is_empty() {
+        return Vec::new();
+    }
+    let mut matcher = Matcher::new(edges, max_cardinality);
+    matcher.solve();
+    let endpoint = &matcher.endpoint;
+    matcher
+        .mate
+        .iter()
+        .map(|&p| {
+            if p >= 0 {
+                Some(endpoint[p as usize])
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Converts a possibly negative index into an index from the front of a list, like Python does
+fn wrap(i: isize, len: usize) -> usize {
+    i.rem_euclid(len as isize) as usize
+}
+
+/// The state of the blossom algorithm
+struct Matcher<'a> {
+    edges: &'a [Edge],
+    max_cardinality: bool,
+    nvertex: usize,
+    /// The vertex at each end of each edge. Edge `k` has endpoints `2k` and `2k + 1`
+    endpoint: Vec<usize>,
+    /// For each vertex, the "remote" endpoints of the incident edges
+    neighbend: Vec<Vec<usize>>,
+    /// For each vertex, the remote endpoint of its matched edge
+    mate: Vec<isize>,
+    /// For each top-level blossom, 0 for unlabeled, 1 for S, and 2 for T
+    label: Vec<u8>,
+    /// The remote endpoint of the edge through which the blossom got its label
+    labelend: Vec<isize>,
+    /// The top-level blossom of each vertex
+    inblossom: Vec<usize>,
+    blossomparent: Vec<isize>,
+    blossomchilds: Vec<Vec<usize>>,
+    blossombase: Vec<isize>,
+    blossomendps: Vec<Vec<usize>>,
+    /// The least-slack edge to a different S-blossom
+    bestedge: Vec<isize>,
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    unusedblossoms: Vec<usize>,
+    dualvar: Vec<i64>,
+    allowedge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(edges: &'a [Edge], max_cardinality: bool) -> Self {
+        let nvertex = edges
+            .iter()
+            .map(|&(i, j, _)| i.max(j) + 1)
+            .max()
+            .unwrap_or(0);
+        let max_weight = edges.iter().map(|&(_, _, w)| w).max().unwrap_or(0).max(0);
+        let endpoint = (0..2 * edges.len())
+            .map(|p| {
+                let (i, j, _) = edges[p / 2];
+                if p % 2 == 0 {
+                    i
+                } else {
+                    j
+                }
+            })
+            .collect();
+        let mut neighbend = vec![Vec::new(); nvertex];
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            neighbend[i].push(2 * k + 1);
+            neighbend[j].push(2 * k);
+        }
+        let mut dualvar = vec![max_weight; nvertex];
+        dualvar.resize(2 * nvertex, 0);
+        let mut blossombase: Vec<isize> = (0..nvertex as isize).collect();
+        blossombase.resize(2 * nvertex, -1);
+        Matcher {
+            edges,
+            max_cardinality,
+            nvertex,
+            endpoint,
+            neighbend,
+            mate: vec![-1; nvertex],
+            label: vec![0; 2 * nvertex],
+            labelend: vec![-1; 2 * nvertex],
+            inblossom: (0..nvertex).collect(),
+            blossomparent: vec![-1; 2 * nvertex],
+            blossomchilds: vec![Vec::new(); 2 * nvertex],
+            blossombase,
+            blossomendps: vec![Vec::new(); 2 * nvertex],
+            bestedge: vec![-1; 2 * nvertex],
+            blossombestedges: vec![None; 2 * nvertex],
+            unusedblossoms: (nvertex..2 * nvertex).collect(),
+            dualvar,
+            allowedge: vec![false; edges.len()],
+            queue: Vec::new(),
+        }
+    }
+
+    /// Returns 2 * the slack of the given edge
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, w) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * w
+    }
+
+    /// Returns all the vertices in the given blossom
+    fn blossom_leaves(&self, b: usize) -> Vec<usize> {
+        let mut digest = Vec::new();
+        let mut stack = vec![b];
+        while let Some(t) = stack.pop() {
+            if t < self.nvertex {
+                digest.push(t);
+            } else {
+                stack.extend(self.blossomchilds[t].iter().rev());
+            }
+        }
+        digest
+    }
+
+    /// Assigns label `t` to the top-level blossom containing vertex `w`, which was reached
+    /// through the edge with remote endpoint `p`
+    fn assign_label(&mut self, w: usize, t: u8, p: isize) {
+        let b = self.inblossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = -1;
+        self.bestedge[b] = -1;
+        if t == 1 {
+            let leaves = self.blossom_leaves(b);
+            self.queue.extend(leaves);
+        } else if t == 2 {
+            let base = self.blossombase[b] as usize;
+            let m = self.mate[base];
+            self.assign_label(self.endpoint[m as usize], 1, m ^ 1);
+        }
+    }
+
+    /// Traces back from vertices `v` and `w` to discover either a new blossom or an augmenting
+    /// path. Returns the base vertex of the new blossom or `-1`.
+    fn scan_blossom(&mut self, mut v: isize, mut w: isize) -> isize {
+        let mut path = Vec::new();
+        let mut base = -1;
+        while v != -1 || w != -1 {
+            let mut b = self.inblossom[v as usize];
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.labelend[b] == -1 {
+                v = -1;
+            } else {
+                v = self.endpoint[self.labelend[b] as usize] as isize;
+                b = self.inblossom[v as usize];
+                v = self.endpoint[self.labelend[b] as usize] as isize;
+            }
+            if w != -1 {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// Constructs a new blossom with the given base, containing edge `k`
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+        let b = self.unusedblossoms.pop().unwrap();
+        self.blossombase[b] = base as isize;
+        self.blossomparent[b] = -1;
+        self.blossomparent[bb] = b as isize;
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb {
+            self.blossomparent[bv] = b as isize;
+            path.push(bv);
+            endps.push(self.labelend[bv] as usize);
+            v = self.endpoint[self.labelend[bv] as usize];
+            bv = self.inblossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        while bw != bb {
+            self.blossomparent[bw] = b as isize;
+            path.push(bw);
+            endps.push((self.labelend[bw] ^ 1) as usize);
+            w = self.endpoint[self.labelend[bw] as usize];
+            bw = self.inblossom[w];
+        }
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+        for v in self.blossom_leaves_of(&path) {
+            if self.label[self.inblossom[v]] == 2 {
+                self.queue.push(v);
+            }
+            self.inblossom[v] = b;
+        }
+        let mut bestedgeto: Vec<isize> = vec![-1; 2 * self.nvertex];
+        for &bv in &path {
+            let nblists: Vec<Vec<usize>> = match self.blossombestedges[bv].take() {
+                Some(list) => vec![list],
+                None => self
+                    .blossom_leaves(bv)
+                    .into_iter()
+                    .map(|v| self.neighbend[v].iter().map(|p| p / 2).collect())
+                    .collect(),
+            };
+            for nblist in nblists {
+                for k in nblist {
+                    let (mut i, mut j, _) = self.edges[k];
+                    if self.inblossom[j] == b {
+                        std::mem::swap(&mut i, &mut j);
+                    }
+                    let bj = self.inblossom[j];
+                    if bj != b
+                        && self.label[bj] == 1
+                        && (bestedgeto[bj] == -1
+                            || self.slack(k) < self.slack(bestedgeto[bj] as usize))
+                    {
+                        bestedgeto[bj] = k as isize;
+                    }
+                }
+            }
+            self.bestedge[bv] = -1;
+        }
+        let best: Vec<usize> = bestedgeto
+            .into_iter()
+            .filter(|&k| k != -1)
+            .map(|k| k as usize)
+            .collect();
+        self.bestedge[b] = -1;
+        for &k in &best {
+            if self.bestedge[b] == -1 || self.slack(k) < self.slack(self.bestedge[b] as usize) {
+                self.bestedge[b] = k as isize;
+            }
+        }
+        self.blossombestedges[b] = Some(best);
+        self.blossomchilds[b] = path;
+        self.blossomendps[b] = endps;
+    }
+
+    /// Returns all vertices in the given sub-blossoms
+    fn blossom_leaves_of(&self, blossoms: &[usize]) -> Vec<usize> {
+        blossoms
+            .iter()
+            .flat_map(|&b| self.blossom_leaves(b))
+            .collect()
+    }
+
+    /// Expands the given top-level blossom
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        let childs = self.blossomchilds[b].clone();
+        for &s in &childs {
+            self.blossomparent[s] = -1;
+            if s < self.nvertex {
+                self.inblossom[s] = s;
+            } else if endstage && self.dualvar[s] == 0 {
+                self.expand_blossom(s, endstage);
+            } else {
+                for v in self.blossom_leaves(s) {
+                    self.inblossom[v] = s;
+                }
+            }
+        }
+        if !endstage && self.label[b] == 2 {
+            let len = childs.len();
+            let endps = self.blossomendps[b].clone();
+            let entrychild = self.inblossom[self.endpoint[(self.labelend[b] ^ 1) as usize]];
+            let mut j = childs.iter().position(|&c| c == entrychild).unwrap() as isize;
+            let (jstep, endptrick): (isize, usize) = if j & 1 != 0 {
+                j -= len as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut p = self.labelend[b] as usize;
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                let q = endps[wrap(j - endptrick as isize, len)];
+                self.label[self.endpoint[q ^ endptrick ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p as isize);
+                self.allowedge[q / 2] = true;
+                j += jstep;
+                p = endps[wrap(j - endptrick as isize, len)] ^ endptrick;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+            let bv = childs[wrap(j, len)];
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.labelend[self.endpoint[p ^ 1]] = p as isize;
+            self.labelend[bv] = p as isize;
+            self.bestedge[bv] = -1;
+            j += jstep;
+            while childs[wrap(j, len)] != entrychild {
+                let bv = childs[wrap(j, len)];
+                if self.label[bv] == 1 {
+                    j += jstep;
+                    continue;
+                }
+                let labeled = self
+                    .blossom_leaves(bv)
+                    .into_iter()
+                    .find(|&v| self.label[v] != 0);
+                if let Some(v) = labeled {
+                    self.label[v] = 0;
+                    let base = self.blossombase[bv] as usize;
+                    self.label[self.endpoint[self.mate[base] as usize]] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+                j += jstep;
+            }
+        }
+        self.label[b] = 0;
+        self.labelend[b] = -1;
+        self.blossomchilds[b] = Vec::new();
+        self.blossomendps[b] = Vec::new();
+        self.blossombase[b] = -1;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = -1;
+        self.unusedblossoms.push(b);
+    }
+
+    /// Swaps matched and unmatched edges over an alternating path through blossom `b` between
+    /// vertex `v` and the base vertex
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossomparent[t] != b as isize {
+            t = self.blossomparent[t] as usize;
+        }
+        if t >= self.nvertex {
+            self.augment_blossom(t, v);
+        }
+        let len = self.blossomchilds[b].len();
+        let i = self.blossomchilds[b].iter().position(|&c| c == t).unwrap();
+        let mut j = i as isize;
+        let (jstep, endptrick): (isize, usize) = if i & 1 != 0 {
+            j -= len as isize;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += jstep;
+            let t = self.blossomchilds[b][wrap(j, len)];
+            let p = self.blossomendps[b][wrap(j - endptrick as isize, len)] ^ endptrick;
+            if t >= self.nvertex {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += jstep;
+            let t = self.blossomchilds[b][wrap(j, len)];
+            if t >= self.nvertex {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = (p ^ 1) as isize;
+            self.mate[self.endpoint[p ^ 1]] = p as isize;
+        }
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    /// Swaps matched and unmatched edges over an alternating path between two single vertices,
+    /// which runs through edge `k`
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.inblossom[s];
+                if bs >= self.nvertex {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p as isize;
+                if self.labelend[bs] == -1 {
+                    break;
+                }
+                let t = self.endpoint[self.labelend[bs] as usize];
+                let bt = self.inblossom[t];
+                s = self.endpoint[self.labelend[bt] as usize];
+                let j = self.endpoint[(self.labelend[bt] ^ 1) as usize];
+                if bt >= self.nvertex {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.labelend[bt];
+                p = (self.labelend[bt] ^ 1) as usize;
+            }
+        }
+    }
+
+    /// Runs the main loop of the algorithm. Each stage finds an augmenting path and uses it to
+    /// improve the matching.
+    fn solve(&mut self) {
+        let n = self.nvertex;
+        for _ in 0..n {
+            self.label.iter_mut().for_each(|l| *l = 0);
+            self.bestedge.iter_mut().for_each(|e| *e = -1);
+            self.blossombestedges[n..]
+                .iter_mut()
+                .for_each(|e| *e = None);
+            self.allowedge.iter_mut().for_each(|e| *e = false);
+            self.queue.clear();
+            for v in 0..n {
+                if self.mate[v] == -1 && self.label[self.inblossom[v]] == 0 {
+                    self.assign_label(v, 1, -1);
+                }
+            }
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let v = match self.queue.pop() {
+                        Some(v) => v,
+                        None => break,
+                    };
+                    for p in self.neighbend[v].clone() {
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] {
+                            continue;
+                        }
+                        let mut kslack = 0;
+                        if !self.allowedge[k] {
+                            kslack = self.slack(k);
+                            if kslack <= 0 {
+                                self.allowedge[k] = true;
+                            }
+                        }
+                        if self.allowedge[k] {
+                            if self.label[self.inblossom[w]] == 0 {
+                                self.assign_label(w, 2, (p ^ 1) as isize);
+                            } else if self.label[self.inblossom[w]] == 1 {
+                                let base = self.scan_blossom(v as isize, w as isize);
+                                if base >= 0 {
+                                    self.add_blossom(base as usize, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.labelend[w] = (p ^ 1) as isize;
+                            }
+                        } else if self.label[self.inblossom[w]] == 1 {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b] == -1
+                                || kslack < self.slack(self.bestedge[b] as usize)
+                            {
+                                self.bestedge[b] = k as isize;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.bestedge[w] == -1
+                                || kslack < self.slack(self.bestedge[w] as usize))
+                        {
+                            self.bestedge[w] = k as isize;
+                        }
+                    }
+                }
+                if augmented {
+                    break;
+                }
+                // No augmenting path was found, so the dual variables need to be updated
+                let mut deltatype = -1;
+                let mut delta = 0;
+                let mut deltaedge = 0;
+                let mut deltablossom = 0;
+                if !self.max_cardinality {
+                    deltatype = 1;
+                    delta = *self.dualvar[..n].iter().min().unwrap();
+                }
+                for v in 0..n {
+                    if self.label[self.inblossom[v]] == 0 && self.bestedge[v] != -1 {
+                        let d = self.slack(self.bestedge[v] as usize);
+                        if deltatype == -1 || d < delta {
+                            delta = d;
+                            deltatype = 2;
+                            deltaedge = self.bestedge[v] as usize;
+                        }
+                    }
+                }
+                for b in 0..2 * n {
+                    if self.blossomparent[b] == -1 && self.label[b] == 1 && self.bestedge[b] != -1 {
+                        let d = self.slack(self.bestedge[b] as usize) / 2;
+                        if deltatype == -1 || d < delta {
+                            delta = d;
+                            deltatype = 3;
+                            deltaedge = self.bestedge[b] as usize;
+                        }
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] >= 0
+                        && self.blossomparent[b] == -1
+                        && self.label[b] == 2
+                        && (deltatype == -1 || self.dualvar[b] < delta)
+                    {
+                        delta = self.dualvar[b];
+                        deltatype = 4;
+                        deltablossom = b;
+                    }
+                }
+                if deltatype == -1 {
+                    // No further improvement is possible
+                    deltatype = 1;
+                    delta = (*self.dualvar[..n].iter().min().unwrap()).max(0);
+                }
+                for v in 0..n {
+                    match self.label[self.inblossom[v]] {
+                        1 => self.dualvar[v] -= delta,
+                        2 => self.dualvar[v] += delta,
+                        _ => {}
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] >= 0 && self.blossomparent[b] == -1 {
+                        match self.label[b] {
+                            1 => self.dualvar[b] += delta,
+                            2 => self.dualvar[b] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+                match deltatype {
+                    1 => break,
+                    2 => {
+                        self.allowedge[deltaedge] = true;
+                        let (mut i, j, _) = self.edges[deltaedge];
+                        if self.label[self.inblossom[i]] == 0 {
+                            i = j;
+                        }
+                        self.queue.push(i);
+                    }
+                    3 => {
+                        self.allowedge[deltaedge] = true;
+                        let (i, _, _) = self.edges[deltaedge];
+                        self.queue.push(i);
+                    }
+                    _ => self.expand_blossom(deltablossom, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+            // Expand all S-blossoms with a dual variable of zero at the end of the stage
+            for b in n..2 * n {
+                if self.blossomparent[b] == -1
+                    && self.blossombase[b] >= 0
+                    && self.label[b] == 1
+                    && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Converts the expected mates, given with `-1` for unmatched vertices, into the output format
+    fn mates(expected: &[isize]) -> Vec<Option<usize>> {
+        expected
+            .iter()
+            .map(|&m| if m >= 0 { Some(m as usize) } else { None })
+            .collect()
+    }
+
+    fn total_weight(edges: &[Edge], mates: &[Option<usize>]) -> i64 {
+        edges
+            .iter()
+            .filter(|&&(i, j, _)| mates[i] == Some(j))
+            .map(|&(_, _, w)| w)
+            .sum()
+    }
+
+    /// Finds the largest weight (and size, if needed) of any matching by trying them all
+    fn brute_force(edges: &[Edge], max_cardinality: bool) -> (usize, i64) {
+        fn search(
+            edges: &[Edge],
+            used: &mut Vec<bool>,
+            size: usize,
+            weight: i64,
+            max_cardinality: bool,
+        ) -> (usize, i64) {
+            let mut best = (size, weight);
+            for (k, &(i, j, w)) in edges.iter().enumerate() {
+                if used[i] || used[j] {
+                    continue;
+                }
+                used[i] = true;
+                used[j] = true;
+                let found = search(&edges[k + 1..], used, size + 1, weight + w, max_cardinality);
+                used[i] = false;
+                used[j] = false;
+                let better = if max_cardinality {
+                    found > best
+                } else {
+                    found.1 > best.1
+                };
+                if better {
+                    best = found;
+                }
+            }
+            best
+        }
+        let n = edges
+            .iter()
+            .map(|&(i, j, _)| i.max(j) + 1)
+            .max()
+            .unwrap_or(0);
+        search(edges, &mut vec![false; n], 0, 0, max_cardinality)
+    }
+
+    #[test]
+    fn empty_and_single_edges() {
+        assert!(max_weight_matching(&[], false).is_empty());
+        assert_eq!(
+            max_weight_matching(&[(0, 1, 1)], false),
+            vec![Some(1), Some(0)]
+        );
+        assert_eq!(
+            max_weight_matching(&[(1, 2, 10), (2, 3, 11)], false),
+            mates(&[-1, -1, 3, 2])
+        );
+        assert_eq!(
+            max_weight_matching(&[(1, 2, 5), (2, 3, 11), (3, 4, 5)], false),
+            mates(&[-1, -1, 3, 2, -1])
+        );
+    }
+
+    #[test]
+    fn max_cardinality() {
+        let edges = [(1, 2, 2), (1, 3, -2), (2, 3, 1), (2, 4, -1), (3, 4, -6)];
+        assert_eq!(
+            max_weight_matching(&edges, false),
+            mates(&[-1, 2, 1, -1, -1])
+        );
+        assert_eq!(max_weight_matching(&edges, true), mates(&[-1, 3, 4, 1, 2]));
+    }
+
+    #[test]
+    fn s_blossom_used_for_augmentation() {
+        let mut edges = vec![(1, 2, 8), (1, 3, 9), (2, 3, 10), (3, 4, 7)];
+        assert_eq!(max_weight_matching(&edges, false), mates(&[-1, 2, 1, 4, 3]));
+        edges.extend([(1, 6, 5), (4, 5, 6)]);
+        assert_eq!(
+            max_weight_matching(&edges, false),
+            mates(&[-1, 6, 3, 2, 5, 4, 1])
+        );
+    }
+
+    #[test]
+    fn s_blossom_relabeled_as_t_blossom() {
+        let edges = [
+            (1, 2, 9),
+            (1, 3, 8),
+            (2, 3, 10),
+            (1, 4, 5),
+            (4, 5, 4),
+            (1, 6, 3),
+        ];
+        assert_eq!(
+            max_weight_matching(&edges, false),
+            mates(&[-1, 6, 3, 2, 5, 4, 1])
+        );
+    }
+
+    #[test]
+    fn nested_s_blossom() {
+        let edges = [
+            (1, 2, 9),
+            (1, 3, 9),
+            (2, 3, 10),
+            (2, 4, 8),
+            (3, 5, 8),
+            (4, 5, 10),
+            (5, 6, 6),
+        ];
+        assert_eq!(
+            max_weight_matching(&edges, false),
+            mates(&[-1, 3, 4, 1, 2, 6, 5])
+        );
+    }
+
+    #[test]
+    fn t_blossom_expanded_and_augmented() {
+        let edges = [
+            (1, 2, 45),
+            (1, 5, 45),
+            (2, 3, 50),
+            (3, 4, 45),
+            (4, 5, 50),
+            (1, 6, 30),
+            (3, 9, 35),
+            (4, 8, 35),
+            (5, 7, 26),
+            (9, 10, 5),
+        ];
+        assert_eq!(
+            max_weight_matching(&edges, false),
+            mates(&[-1, 6, 3, 2, 8, 7, 1, 5, 4, 10, 9])
+        );
+    }
+
+    #[test]
+    fn nested_blossom_with_alternating_path() {
+        let edges = [
+            (1, 2, 45),
+            (1, 7, 45),
+            (2, 3, 50),
+            (3, 4, 45),
+            (4, 5, 95),
+            (4, 6, 94),
+            (5, 6, 94),
+            (6, 7, 50),
+            (1, 8, 30),
+            (3, 11, 35),
+            (5, 9, 36),
+            (7, 10, 26),
+            (11, 12, 5),
+        ];
+        assert_eq!(
+            max_weight_matching(&edges, false),
+            mates(&[-1, 8, 3, 2, 6, 9, 4, 10, 1, 5, 7, 12, 11])
+        );
+    }
+
+    #[test]
+    fn nested_s_blossom_relabeled_and_expanded() {
+        let edges = [
+            (1, 2, 40),
+            (1, 3, 40),
+            (2, 3, 60),
+            (2, 4, 55),
+            (3, 5, 55),
+            (4, 5, 50),
+            (1, 8, 15),
+            (5, 7, 30),
+            (7, 6, 10),
+            (8, 10, 10),
+            (4, 9, 30),
+        ];
+        assert_eq!(
+            max_weight_matching(&edges, false),
+            mates(&[-1, 2, 1, 5, 9, 3, 7, 6, 10, 4, 8])
+        );
+    }
+
+    #[test]
+    fn odd_cycles_match_brute_force() {
+        // A simple linear congruential generator keeps the graphs the same between runs
+        let mut state: u64 = 0x5eed;
+        let mut next = |bound: u64| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % bound
+        };
+        for _ in 0..200 {
+            let n = 3 + next(6) as usize;
+            let mut edges = Vec::new();
+            for i in 0..n {
+                for j in i + 1..n {
+                    if next(3) != 0 {
+                        edges.push((i, j, next(20) as i64));
+                    }
+                }
+            }
+            if edges.is_empty() {
+                continue;
+            }
+            for max_cardinality in [false, true] {
+                let found = max_weight_matching(&edges, max_cardinality);
+                for (i, mate) in found.iter().enumerate() {
+                    if let Some(j) = mate {
+                        assert_eq!(found[*j], Some(i));
+                    }
+                }
+                let size = found.iter().flatten().count() / 2;
+                let (best_size, best_weight) = brute_force(&edges, max_cardinality);
+                if max_cardinality {
+                    assert_eq!(size, best_size, "{edges:?}");
+                }
+                assert_eq!(total_weight(&edges, &found), best_weight, "{edges:?}");
+            }
+        }
+    }
+}
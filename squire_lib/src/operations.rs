@@ -1,14 +1,20 @@
-use std::time::Duration;
+use std::{
+    mem::discriminant,
+    time::{Duration, SystemTime},
+};
 
-use uuid::Uuid;
+use uuid::{Builder, Uuid};
 
 use crate::{
+    accounts::{AccountId, UserAccount},
     player::{Player, PlayerId},
     player_registry::PlayerIdentifier,
     round::{Round, RoundId, RoundResult, RoundStatus},
     round_registry::RoundIdentifier,
-    settings::TournamentSetting,
+    scoring::seeded_draw,
+    settings::{self, TournamentSetting},
     swiss_pairings::TournamentError,
+    tournament::TournamentPreset,
 };
 
 use mtgjson::model::deck::Deck;
@@ -28,11 +34,13 @@ pub enum TournOp {
     RegisterPlayer(String),
     RecordResult(RoundIdentifier, RoundResult),
     ConfirmResult(PlayerIdentifier),
+    DisputeResult(PlayerIdentifier),
     DropPlayer(PlayerIdentifier),
     AdminDropPlayer(PlayerIdentifier),
     AddDeck(PlayerIdentifier, String, Deck),
     RemoveDeck(PlayerIdentifier, String),
     RemoveRound(RoundIdentifier),
+    CertifyRound(RoundIdentifier, Vec<RoundResult>),
     SetGamerTag(PlayerIdentifier, String),
     ReadyPlayer(PlayerIdentifier),
     UnReadyPlayer(PlayerIdentifier),
@@ -41,11 +49,33 @@ pub enum TournOp {
     CreateRound(Vec<PlayerIdentifier>),
     PairRound(),
     TimeExtension(RoundIdentifier, Duration),
+    PauseRound(RoundIdentifier),
+    ResumeRound(RoundIdentifier),
+    CallTime(RoundIdentifier),
+    TakeExtraTurn(RoundIdentifier),
     Cut(usize),
     PruneDecks(),
     PrunePlayers(),
     ImportPlayer(Player),
     ImportRound(Round),
+    RegisterJudge(UserAccount),
+    RegisterAdmin(UserAccount),
+    AddPhase(TournamentPreset, Duration),
+    NextPhase(),
+    RegisterTeam(String, Vec<String>),
+    AddMemberDeck(PlayerIdentifier, usize, String, Deck),
+    RemoveMemberDeck(PlayerIdentifier, usize, String),
+    RegisterPlayerAccount(UserAccount),
+}
+
+/// The least privileged role that is allowed to submit an operation. Admins can submit any
+/// operation, judges can submit judge and player operations, and players can only submit player
+/// operations that act on themselves.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpRole {
+    Player,
+    Judge,
+    Admin,
 }
 
 impl TournOp {
@@ -59,19 +89,31 @@ impl TournOp {
             | End()
             | Cancel()
             | RegisterPlayer(_)
+            | RegisterTeam(_, _)
+            | RegisterPlayerAccount(_)
             | UpdateTournSetting(_)
             | PairRound()
             | TimeExtension(_, _)
+            | PauseRound(_)
+            | ResumeRound(_)
+            | CallTime(_)
+            | TakeExtraTurn(_)
             | Cut(_)
             | PruneDecks()
             | PrunePlayers()
             | RemoveRound(_)
             | ImportPlayer(_)
             | ImportRound(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | AddPhase(_, _)
+            | NextPhase()
             | RecordResult(_, _)
+            | CertifyRound(_, _)
             | CreateRound(_) => self,
             CheckIn(_) => Self::CheckIn(ident),
             ConfirmResult(_) => Self::ConfirmResult(ident),
+            DisputeResult(_) => Self::DisputeResult(ident),
             DropPlayer(_) => Self::DropPlayer(ident),
             AdminDropPlayer(_) => Self::AdminDropPlayer(ident),
             AddDeck(_, name, deck) => Self::AddDeck(ident, name, deck),
@@ -80,6 +122,8 @@ impl TournOp {
             ReadyPlayer(_) => Self::ReadyPlayer(ident),
             UnReadyPlayer(_) => Self::UnReadyPlayer(ident),
             GiveBye(_) => Self::GiveBye(ident),
+            AddMemberDeck(_, seat, name, deck) => Self::AddMemberDeck(ident, seat, name, deck),
+            RemoveMemberDeck(_, seat, name) => Self::RemoveMemberDeck(ident, seat, name),
         }
     }
 
@@ -93,22 +137,36 @@ impl TournOp {
             | End()
             | Cancel()
             | RegisterPlayer(_)
+            | RegisterTeam(_, _)
+            | RegisterPlayerAccount(_)
             | UpdateTournSetting(_)
             | PairRound()
             | TimeExtension(_, _)
+            | PauseRound(_)
+            | ResumeRound(_)
+            | CallTime(_)
+            | TakeExtraTurn(_)
             | Cut(_)
             | PruneDecks()
             | PrunePlayers()
             | RemoveRound(_)
             | RecordResult(_, _)
+            | CertifyRound(_, _)
             | CheckIn(_)
             | ImportPlayer(_)
             | ImportRound(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | AddPhase(_, _)
+            | NextPhase()
             | ConfirmResult(_)
+            | DisputeResult(_)
             | DropPlayer(_)
             | AdminDropPlayer(_)
             | AddDeck(_, _, _)
             | RemoveDeck(_, _)
+            | AddMemberDeck(_, _, _, _)
+            | RemoveMemberDeck(_, _, _)
             | SetGamerTag(_, _)
             | ReadyPlayer(_)
             | UnReadyPlayer(_)
@@ -128,17 +186,26 @@ impl TournOp {
             | Cancel()
             | CheckIn(_)
             | RegisterPlayer(_)
+            | RegisterTeam(_, _)
+            | RegisterPlayerAccount(_)
             | ConfirmResult(_)
+            | DisputeResult(_)
             | DropPlayer(_)
             | AdminDropPlayer(_)
             | AddDeck(_, _, _)
             | RemoveDeck(_, _)
+            | AddMemberDeck(_, _, _, _)
+            | RemoveMemberDeck(_, _, _)
             | RemoveRound(_)
             | SetGamerTag(_, _)
             | ReadyPlayer(_)
             | UnReadyPlayer(_)
             | ImportPlayer(_)
             | ImportRound(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | AddPhase(_, _)
+            | NextPhase()
             | UpdateTournSetting(_)
             | GiveBye(_)
             | CreateRound(_)
@@ -147,7 +214,12 @@ impl TournOp {
             | PruneDecks()
             | PrunePlayers() => self,
             TimeExtension(_, dur) => TimeExtension(ident, dur),
+            PauseRound(_) => PauseRound(ident),
+            ResumeRound(_) => ResumeRound(ident),
+            CallTime(_) => CallTime(ident),
+            TakeExtraTurn(_) => TakeExtraTurn(ident),
             RecordResult(_, res) => RecordResult(ident, res),
+            CertifyRound(_, res) => CertifyRound(ident, res),
         }
     }
 
@@ -161,23 +233,37 @@ impl TournOp {
             | End()
             | Cancel()
             | RegisterPlayer(_)
+            | RegisterTeam(_, _)
+            | RegisterPlayerAccount(_)
             | UpdateTournSetting(_)
             | PairRound()
             | TimeExtension(_, _)
+            | PauseRound(_)
+            | ResumeRound(_)
+            | CallTime(_)
+            | TakeExtraTurn(_)
             | Cut(_)
             | PruneDecks()
             | ImportPlayer(_)
             | ImportRound(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | AddPhase(_, _)
+            | NextPhase()
             | PrunePlayers()
             | RemoveRound(_)
             | RecordResult(_, _)
+            | CertifyRound(_, _)
             | CreateRound(_) => None,
             CheckIn(ident)
             | ConfirmResult(ident)
+            | DisputeResult(ident)
             | DropPlayer(ident)
             | AdminDropPlayer(ident)
             | AddDeck(ident, _, _)
             | RemoveDeck(ident, _)
+            | AddMemberDeck(ident, _, _, _)
+            | RemoveMemberDeck(ident, _, _)
             | SetGamerTag(ident, _)
             | ReadyPlayer(ident)
             | UnReadyPlayer(ident)
@@ -196,26 +282,40 @@ impl TournOp {
             | End()
             | Cancel()
             | RegisterPlayer(_)
+            | RegisterTeam(_, _)
+            | RegisterPlayerAccount(_)
             | UpdateTournSetting(_)
             | PairRound()
             | TimeExtension(_, _)
+            | PauseRound(_)
+            | ResumeRound(_)
+            | CallTime(_)
+            | TakeExtraTurn(_)
             | Cut(_)
             | PruneDecks()
             | ImportPlayer(_)
             | ImportRound(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | AddPhase(_, _)
+            | NextPhase()
             | CheckIn(_)
             | ConfirmResult(_)
+            | DisputeResult(_)
             | DropPlayer(_)
             | AdminDropPlayer(_)
             | AddDeck(_, _, _)
             | RemoveDeck(_, _)
+            | AddMemberDeck(_, _, _, _)
+            | RemoveMemberDeck(_, _, _)
             | SetGamerTag(_, _)
             | ReadyPlayer(_)
             | UnReadyPlayer(_)
             | GiveBye(_)
             | PrunePlayers()
             | RemoveRound(_)
-            | RecordResult(_, _) => None,
+            | RecordResult(_, _)
+            | CertifyRound(_, _) => None,
             CreateRound(idents) => Some(idents.clone()),
         }
     }
@@ -231,15 +331,24 @@ impl TournOp {
             | Cancel()
             | CheckIn(_)
             | RegisterPlayer(_)
+            | RegisterTeam(_, _)
+            | RegisterPlayerAccount(_)
             | ConfirmResult(_)
+            | DisputeResult(_)
             | DropPlayer(_)
             | AdminDropPlayer(_)
             | AddDeck(_, _, _)
             | RemoveDeck(_, _)
+            | AddMemberDeck(_, _, _, _)
+            | RemoveMemberDeck(_, _, _)
             | RemoveRound(_)
             | SetGamerTag(_, _)
             | ImportPlayer(_)
             | ImportRound(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | AddPhase(_, _)
+            | NextPhase()
             | ReadyPlayer(_)
             | UnReadyPlayer(_)
             | UpdateTournSetting(_)
@@ -249,7 +358,64 @@ impl TournOp {
             | Cut(_)
             | PruneDecks()
             | PrunePlayers() => None,
-            TimeExtension(ident, _) | RecordResult(ident, _) => Some(ident.clone()),
+            TimeExtension(ident, _)
+            | PauseRound(ident)
+            | ResumeRound(ident)
+            | CallTime(ident)
+            | TakeExtraTurn(ident)
+            | RecordResult(ident, _)
+            | CertifyRound(ident, _) => Some(ident.clone()),
+        }
+    }
+
+    /// Returns the least privileged role that can submit this operation
+    pub fn required_role(&self) -> OpRole {
+        use TournOp::*;
+        match self {
+            CheckIn(_)
+            | RecordResult(_, _)
+            | ConfirmResult(_)
+            | DisputeResult(_)
+            | DropPlayer(_)
+            | AddDeck(_, _, _)
+            | RemoveDeck(_, _)
+            | AddMemberDeck(_, _, _, _)
+            | RemoveMemberDeck(_, _, _)
+            | SetGamerTag(_, _)
+            | ReadyPlayer(_)
+            | UnReadyPlayer(_)
+            | RegisterPlayerAccount(_) => OpRole::Player,
+            // Players register themselves through their accounts, so registering someone by name
+            // is left to judges
+            RegisterPlayer(_)
+            | RegisterTeam(_, _)
+            | AdminDropPlayer(_)
+            | RemoveRound(_)
+            | CertifyRound(_, _)
+            | GiveBye(_)
+            | CreateRound(_)
+            | TimeExtension(_, _)
+            | PauseRound(_)
+            | ResumeRound(_)
+            | CallTime(_)
+            | TakeExtraTurn(_) => OpRole::Judge,
+            UpdateReg(_)
+            | Start()
+            | Freeze()
+            | Thaw()
+            | End()
+            | Cancel()
+            | UpdateTournSetting(_)
+            | PairRound()
+            | Cut(_)
+            | PruneDecks()
+            | PrunePlayers()
+            | ImportPlayer(_)
+            | ImportRound(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | AddPhase(_, _)
+            | NextPhase() => OpRole::Admin,
         }
     }
 }
@@ -269,11 +435,45 @@ pub type OpResult = Result<OpData, TournamentError>;
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct OpId(Uuid);
 
+/// The time and ids used while applying an operation. Anything that an operation creates, e.g. a
+/// new player's id or a new round's timer, comes from here instead of the system clock or a random
+/// id. Replaying an operation with the same context always gives the same tournament.
+#[derive(Debug, Clone)]
+pub struct OpContext {
+    pub time: SystemTime,
+    salt: Uuid,
+    count: u64,
+}
+
+/// The entity that submitted an operation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum OpAuthor {
+    Player(PlayerId),
+    Judge(AccountId),
+    Admin(AccountId),
+    /// An account that has no role in the tournament. These can only register themselves.
+    Account(AccountId),
+    System,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct FullOp {
     pub(crate) op: TournOp,
     pub(crate) id: OpId,
     pub(crate) active: bool,
+    pub(crate) author: OpAuthor,
+    pub(crate) time: SystemTime,
+    pub(crate) reason: Option<String>,
+}
+
+/// A filter used to query the operation log. Every field that is set must match for an operation
+/// to pass the filter.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AuditFilter {
+    pub author: Option<OpAuthor>,
+    pub after: Option<SystemTime>,
+    pub before: Option<SystemTime>,
+    pub player: Option<PlayerId>,
 }
 
 /// An ordered list of all operations applied to a tournament
@@ -298,7 +498,7 @@ pub struct OpSync {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SyncStatus {
     SyncError(OpSync), // Unknown starting operation
-    InProgress(Blockage),
+    InProgress(Box<Blockage>),
     Completed(Synced),
 }
 
@@ -358,7 +558,7 @@ impl Blockage {
         } else if op == self.problem.1 {
             self.agreed.add_op(self.problem.1);
         } else {
-            return SyncStatus::InProgress(self);
+            return SyncStatus::InProgress(Box::new(self));
         }
         match self.known.merge(self.other) {
             Ok(slice) => {
@@ -391,7 +591,7 @@ impl Blockage {
             self.agreed.add_op(self.problem.1);
             self.agreed.add_op(self.problem.0);
         } else {
-            return SyncStatus::InProgress(self);
+            return SyncStatus::InProgress(Box::new(self));
         }
         match self.known.merge(self.other) {
             Ok(slice) => {
@@ -424,17 +624,10 @@ impl OpLog {
     /// Creates a slice of this log starting at the given index. `None` is returned if `index` is
     /// out of bounds.
     pub fn get_slice(&self, id: OpId) -> Option<OpSlice> {
-        let mut pass_through = false;
-        let ops = self
-            .ops
-            .iter()
-            .filter(|o| {
-                pass_through |= o.id == id;
-                pass_through
-            })
-            .cloned()
-            .collect();
-        Some(OpSlice { ops })
+        let index = self.ops.iter().position(|o| o.id == id)?;
+        Some(OpSlice {
+            ops: self.ops[index..].to_vec(),
+        })
     }
 
     /// Removes all elements in the log starting at the first index of the given slice. All operations in the slice are then appended to the end of the log.
@@ -453,19 +646,20 @@ impl OpLog {
     ///
     /// The primary use case for this is to rollback a round pairing in a Swiss tournament.
     pub fn rollback(&self, mut f: impl FnMut(&FullOp) -> Option<bool>) -> Rollback {
-        let l = self.ops.len();
-        let ops = self
+        let mut ops: Vec<FullOp> = self
             .ops
             .iter()
             .rev()
             .map_while(|o| {
                 f(o).map(|b| {
                     let mut op = o.clone();
-                    op.active = b;
+                    op.active = !b;
                     op
                 })
             })
             .collect();
+        // The slice needs to be in the same order as the log
+        ops.reverse();
         Rollback {
             ops: OpSlice { ops },
         }
@@ -542,38 +736,37 @@ impl OpSlice {
     /// The new log is then returned.
     ///
     /// Every operation "knows" what it blocks.
-    pub fn merge(mut self, mut other: OpSlice) -> Result<Self, Blockage> {
-        todo!()
-        /*
-        let mut merged = OpSlice::new();
-        for (i, (_, other_op)) in other.ops.clone().iter().enumerate() {
-            let mut iter = self
+    pub fn merge(mut self, other: OpSlice) -> Result<Self, Box<Blockage>> {
+        let mut agreed = OpSlice::new();
+        let mut others = other.ops.into_iter();
+        while let Some(other_op) = others.next() {
+            let index = self
                 .ops
                 .iter()
-                .enumerate()
-                .take_while(|(i, (_, o))| o != other_op && !o.blocks(other_op));
-            let (_, other_op) = other.ops.remove(i);
-            if let Some((i, (_, this_op))) = iter.next() {
-                let (_, this_op) = self.ops.remove(i);
-                if this_op == other_op {
-                    merged.add_op(this_op);
-                } else {
-                    return Err(Blockage {
-                        known: self,
-                        agreed: merged,
-                        other,
-                        problem: (this_op, other_op),
-                    });
+                .position(|o| o.id == other_op.id || o.blocks(&other_op));
+            match index {
+                Some(i) => {
+                    let this_op = self.ops.remove(i);
+                    if this_op.id == other_op.id {
+                        agreed.add_op(this_op);
+                    } else {
+                        return Err(Box::new(Blockage {
+                            known: self,
+                            agreed,
+                            other: OpSlice {
+                                ops: others.collect(),
+                            },
+                            problem: (this_op, other_op),
+                        }));
+                    }
+                }
+                None => {
+                    agreed.add_op(other_op);
                 }
-            } else {
-                merged.add_op(other_op);
             }
         }
-        for (_, o) in self.ops {
-            merged.add_op(o);
-        }
-        Ok(merged)
-            */
+        agreed.ops.extend(self.ops);
+        Ok(agreed)
     }
 }
 
@@ -589,24 +782,586 @@ impl Default for OpSlice {
     }
 }
 
+impl OpContext {
+    /// Creates a context whose ids are derived from the given salt
+    pub fn new(salt: Uuid, time: SystemTime) -> Self {
+        OpContext {
+            time,
+            salt,
+            count: 0,
+        }
+    }
+
+    /// Generates the next id for something that the operation creates
+    pub fn next_id(&mut self) -> Uuid {
+        self.count += 1;
+        let salt = self.salt.as_u128();
+        let (high, low) = ((salt >> 64) as u64, salt as u64);
+        let bytes = ((seeded_draw(high, &(low, self.count)) as u128) << 64)
+            | seeded_draw(low, &(high, self.count)) as u128;
+        Builder::from_random_bytes(bytes.to_le_bytes()).into_uuid()
+    }
+}
+
 impl FullOp {
-    pub fn new(op: TournOp) -> Self {
+    /// The context that the operation is applied in. The ids that the operation generates are
+    /// derived from the operation's id.
+    pub fn context(&self) -> OpContext {
+        OpContext::new(self.id.0, self.time)
+    }
+
+    pub fn new(op: TournOp, author: OpAuthor, reason: Option<String>) -> Self {
         Self {
             op,
             id: OpId(Uuid::new_v4()),
             active: true,
+            author,
+            time: SystemTime::now(),
+            reason,
         }
     }
 
-    /// Determines if the given operation affects this operation
+    /// Returns the underlying tournament operation
+    pub fn op(&self) -> &TournOp {
+        &self.op
+    }
+
+    /// Returns the id of the operation
+    pub fn id(&self) -> OpId {
+        self.id
+    }
+
+    /// Returns who submitted the operation
+    pub fn author(&self) -> &OpAuthor {
+        &self.author
+    }
+
+    /// Returns when the operation was submitted
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Returns the reason given for the operation, if any
+    pub fn reason(&self) -> Option<&String> {
+        self.reason.as_ref()
+    }
+
+    /// Determines if the given operation affects this operation. Inactive (rolled back)
+    /// operations never block.
     pub fn blocks(&self, other: &Self) -> bool {
-        self.op.blocks(&other.op)
+        self.active
+            && other.active
+            && (self.op.blocks(&other.op)
+                || self.grants(&other.author)
+                || other.grants(&self.author))
+    }
+
+    /// Returns `true` if this operation gives the author the role they need to submit operations,
+    /// i.e. an op by a judge can't be moved before the op that registered them.
+    fn grants(&self, author: &OpAuthor) -> bool {
+        match (&self.op, author) {
+            (
+                TournOp::RegisterJudge(acc) | TournOp::RegisterAdmin(acc),
+                OpAuthor::Judge(id) | OpAuthor::Admin(id),
+            ) => &acc.external_id == id,
+            _ => false,
+        }
+    }
+}
+
+impl AuditFilter {
+    /// Checks the author and time of the given operation against the filter. Since the players
+    /// affected by an operation can depend on the state of the tournament, the player filter is
+    /// not checked here.
+    pub fn matches(&self, op: &FullOp) -> bool {
+        self.author
+            .as_ref()
+            .map(|a| a == &op.author)
+            .unwrap_or(true)
+            && self.after.map(|t| op.time >= t).unwrap_or(true)
+            && self.before.map(|t| op.time <= t).unwrap_or(true)
     }
 }
 
 impl TournOp {
-    /// Determines if the given operation affects this operation
+    /// Determines if the given operation affects this operation. Two operations block each other
+    /// if applying them in a different order could result in a meaningfully different tournament.
+    /// This relation is symmetric.
     pub fn blocks(&self, other: &Self) -> bool {
-        todo!()
+        if self.is_global() || other.is_global() {
+            return true;
+        }
+        if self.creates_rounds() && other.creates_rounds() {
+            return true;
+        }
+        self.blocks_one_way(other) || other.blocks_one_way(self)
+    }
+
+    /// Returns `true` if the operation changes the status of the tournament or acts on every
+    /// player or round at once. These operations can't be reordered around any other operation.
+    fn is_global(&self) -> bool {
+        use TournOp::*;
+        matches!(
+            self,
+            Start()
+                | Freeze()
+                | Thaw()
+                | End()
+                | Cancel()
+                | PairRound()
+                | Cut(_)
+                | PruneDecks()
+                | PrunePlayers()
+                | NextPhase()
+        )
+    }
+
+    /// Returns `true` if the operation can create a round. The match and table numbers of a round
+    /// depend on the order in which rounds are created.
+    fn creates_rounds(&self) -> bool {
+        use TournOp::*;
+        matches!(
+            self,
+            PairRound() | GiveBye(_) | CreateRound(_) | ReadyPlayer(_) | ImportRound(_)
+        )
+    }
+
+    /// Returns all players that this operation refers to
+    fn player_idents(&self) -> Vec<PlayerIdentifier> {
+        match (self.get_player_ident(), self.list_player_ident()) {
+            (Some(ident), _) => vec![ident],
+            (None, Some(idents)) => idents,
+            (None, None) => Vec::new(),
+        }
+    }
+
+    /// Determines if this operation affects the given operation. The inverse relation is not
+    /// checked.
+    fn blocks_one_way(&self, other: &Self) -> bool {
+        use TournOp::*;
+        match self {
+            Start() | Freeze() | Thaw() | End() | Cancel() | PairRound() | Cut(_)
+            | PruneDecks() | PrunePlayers() | NextPhase() => true,
+            // Phases are played in the order that they are added
+            AddPhase(_, _) => matches!(other, AddPhase(_, _)),
+            UpdateReg(_) => matches!(
+                other,
+                UpdateReg(_)
+                    | RegisterPlayer(_)
+                    | RegisterTeam(_, _)
+                    | RegisterPlayerAccount(_)
+                    | AddDeck(_, _, _)
+                    | AddMemberDeck(_, _, _, _)
+            ),
+            UpdateTournSetting(s) => match other {
+                UpdateTournSetting(o) => same_setting(s, o),
+                // Registration depends on the team size
+                RegisterPlayer(_) | RegisterTeam(_, _) | RegisterPlayerAccount(_) => {
+                    matches!(s, TournamentSetting::TeamSize(_))
+                }
+                // Results are checked against the number of games in a match
+                RecordResult(_, _) | CertifyRound(_, _) => {
+                    matches!(s, TournamentSetting::BestOf(_))
+                }
+                _ => other.creates_rounds(),
+            },
+            RegisterPlayer(name) | RegisterTeam(name, _) => match other {
+                RegisterPlayer(n) | RegisterTeam(n, _) => n == name,
+                RegisterPlayerAccount(acc) => &acc.account_name == name,
+                ImportPlayer(plyr) => &plyr.name == name,
+                _ => other
+                    .player_idents()
+                    .contains(&PlayerIdentifier::Name(name.clone())),
+            },
+            ImportPlayer(plyr) => match other {
+                ImportPlayer(p) => p.id == plyr.id || p.name == plyr.name,
+                RegisterTeam(name, _) => &plyr.name == name,
+                RegisterPlayerAccount(acc) => plyr.name == acc.account_name,
+                _ => other.player_idents().iter().any(|ident| match ident {
+                    PlayerIdentifier::Id(id) => id == &plyr.id,
+                    PlayerIdentifier::Name(name) => name == &plyr.name,
+                }),
+            },
+            // Account players are named after their account
+            RegisterPlayerAccount(account) => match other {
+                RegisterPlayerAccount(acc) => {
+                    acc.external_id == account.external_id
+                        || acc.account_name == account.account_name
+                }
+                _ => RegisterPlayer(account.account_name.clone()).blocks_one_way(other),
+            },
+            ImportRound(rnd) => match other.get_match_ident() {
+                Some(RoundIdentifier::Id(id)) => id == rnd.id,
+                Some(RoundIdentifier::Number(num)) => num == rnd.match_number,
+                None => false,
+            },
+            RecordResult(r_ident, _) | CertifyRound(r_ident, _) => match other {
+                RecordResult(ident, _) | CertifyRound(ident, _) | RemoveRound(ident) => {
+                    same_round(ident, r_ident)
+                }
+                // Confirmations are made by player, so we can't tell which round is confirmed
+                ConfirmResult(_) | DisputeResult(_) => true,
+                _ => false,
+            },
+            RemoveRound(r_ident) => match other {
+                RecordResult(ident, _)
+                | CertifyRound(ident, _)
+                | TimeExtension(ident, _)
+                | PauseRound(ident)
+                | ResumeRound(ident)
+                | CallTime(ident)
+                | TakeExtraTurn(ident)
+                | RemoveRound(ident) => same_round(ident, r_ident),
+                ConfirmResult(_) | DisputeResult(_) => true,
+                _ => false,
+            },
+            DropPlayer(p_ident) | AdminDropPlayer(p_ident) => match other {
+                ReadyPlayer(ident) | ConfirmResult(ident) | DisputeResult(ident) => {
+                    same_player(ident, p_ident)
+                }
+                // Rounds are identified separately from their players, so we can't tell if the
+                // dropped player is in the round
+                RecordResult(_, _) | CertifyRound(_, _) => true,
+                _ => false,
+            },
+            AddDeck(p_ident, _, _) | RemoveDeck(p_ident, _) => {
+                matches!(
+                    other,
+                    AddDeck(ident, _, _) | RemoveDeck(ident, _) if same_player(ident, p_ident)
+                )
+            }
+            AddMemberDeck(p_ident, p_seat, _, _) | RemoveMemberDeck(p_ident, p_seat, _) => {
+                matches!(
+                    other,
+                    AddMemberDeck(ident, seat, _, _) | RemoveMemberDeck(ident, seat, _)
+                        if same_player(ident, p_ident) && seat == p_seat
+                )
+            }
+            SetGamerTag(p_ident, _) => {
+                matches!(other, SetGamerTag(ident, _) if same_player(ident, p_ident))
+            }
+            // The state of a round's clock depends on the order of these
+            PauseRound(r_ident)
+            | ResumeRound(r_ident)
+            | CallTime(r_ident)
+            | TakeExtraTurn(r_ident) => {
+                matches!(
+                    other,
+                    PauseRound(ident) | ResumeRound(ident) | CallTime(ident) | TakeExtraTurn(ident) | RemoveRound(ident)
+                        if same_round(ident, r_ident)
+                )
+            }
+            // A dispute stops any further confirmations of the round
+            DisputeResult(_) => matches!(other, ConfirmResult(_) | DisputeResult(_)),
+            // Readying a player can trigger pairings, which depends on who else is ready
+            ReadyPlayer(_) => matches!(other, UnReadyPlayer(_)),
+            CheckIn(_)
+            | ConfirmResult(_)
+            | UnReadyPlayer(_)
+            | RegisterJudge(_)
+            | RegisterAdmin(_)
+            | GiveBye(_)
+            | CreateRound(_)
+            | TimeExtension(_, _) => false,
+        }
+    }
+}
+
+/// Determines if two identifiers could refer to the same round. An id and a match number can't be
+/// compared without the tournament, so they are assumed to match.
+fn same_round(this: &RoundIdentifier, other: &RoundIdentifier) -> bool {
+    match (this, other) {
+        (RoundIdentifier::Id(this), RoundIdentifier::Id(other)) => this == other,
+        (RoundIdentifier::Number(this), RoundIdentifier::Number(other)) => this == other,
+        _ => true,
+    }
+}
+
+/// Determines if two identifiers could refer to the same player. An id and a name can't be
+/// compared without the tournament, so they are assumed to match.
+fn same_player(this: &PlayerIdentifier, other: &PlayerIdentifier) -> bool {
+    match (this, other) {
+        (PlayerIdentifier::Id(this), PlayerIdentifier::Id(other)) => this == other,
+        (PlayerIdentifier::Name(this), PlayerIdentifier::Name(other)) => this == other,
+        _ => true,
+    }
+}
+
+/// Determines if two settings update the same value
+fn same_setting(this: &TournamentSetting, other: &TournamentSetting) -> bool {
+    use TournamentSetting::*;
+    match (this, other) {
+        (PairingSetting(this), PairingSetting(other)) => match (this, other) {
+            (settings::PairingSetting::Swiss(this), settings::PairingSetting::Swiss(other)) => {
+                discriminant(this) == discriminant(other)
+            }
+            (settings::PairingSetting::Fluid(this), settings::PairingSetting::Fluid(other)) => {
+                discriminant(this) == discriminant(other)
+            }
+            (
+                settings::PairingSetting::Elimination(this),
+                settings::PairingSetting::Elimination(other),
+            ) => discriminant(this) == discriminant(other),
+            _ => false,
+        },
+        (ScoringSetting(this), ScoringSetting(other)) => match (this, other) {
+            (
+                settings::ScoringSetting::Standard(this),
+                settings::ScoringSetting::Standard(other),
+            ) => discriminant(this) == discriminant(other),
+            (
+                settings::ScoringSetting::Buchholz(this),
+                settings::ScoringSetting::Buchholz(other),
+            ) => discriminant(this) == discriminant(other),
+            (
+                settings::ScoringSetting::SonnebornBerger(this),
+                settings::ScoringSetting::SonnebornBerger(other),
+            ) => discriminant(this) == discriminant(other),
+            (
+                settings::ScoringSetting::Cumulative(this),
+                settings::ScoringSetting::Cumulative(other),
+            ) => discriminant(this) == discriminant(other),
+            (
+                settings::ScoringSetting::Placement(this),
+                settings::ScoringSetting::Placement(other),
+            ) => discriminant(this) == discriminant(other),
+            _ => false,
+        },
+        // Changing the scoring system resets its settings
+        (ScoringPreset(_), ScoringSetting(_)) | (ScoringSetting(_), ScoringPreset(_)) => true,
+        _ => discriminant(this) == discriminant(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::Player;
+
+    fn full_op(op: TournOp) -> FullOp {
+        FullOp::new(op, OpAuthor::System, None)
+    }
+
+    fn authored(op: TournOp, author: OpAuthor) -> FullOp {
+        FullOp::new(op, author, None)
+    }
+
+    fn account() -> UserAccount {
+        UserAccount {
+            external_id: AccountId(Uuid::new_v4()),
+            display_name: "judge".to_owned(),
+            account_name: "judge".to_owned(),
+        }
+    }
+
+    /// Merges two slices that only share the first op and returns the conflicting pair
+    fn conflict(shared: &FullOp, local: &FullOp, remote: &FullOp) -> Option<(FullOp, FullOp)> {
+        slice(&[shared, local])
+            .merge(slice(&[shared, remote]))
+            .err()
+            .map(|block| block.problem())
+    }
+
+    fn slice(ops: &[&FullOp]) -> OpSlice {
+        OpSlice {
+            ops: ops.iter().map(|o| (*o).clone()).collect(),
+        }
+    }
+
+    fn round_id() -> RoundIdentifier {
+        RoundIdentifier::Id(Round::new(0, 0, Duration::from_secs(0)).id)
+    }
+
+    fn player_id(name: &str) -> PlayerIdentifier {
+        PlayerIdentifier::Id(Player::new(name.to_owned()).id)
+    }
+
+    #[test]
+    fn results_for_different_rounds_commute() {
+        let (a, b) = (round_id(), round_id());
+        let one = TournOp::RecordResult(a, RoundResult::Draw());
+        let two = TournOp::RecordResult(b, RoundResult::Draw());
+        assert!(!one.blocks(&two));
+        let one = TournOp::RecordResult(RoundIdentifier::Number(0), RoundResult::Draw());
+        let two = TournOp::RecordResult(RoundIdentifier::Number(1), RoundResult::Draw());
+        assert!(!one.blocks(&two));
+    }
+
+    #[test]
+    fn results_for_the_same_round_block() {
+        let a = round_id();
+        let one = TournOp::RecordResult(a.clone(), RoundResult::Draw());
+        let two = TournOp::RemoveRound(a);
+        assert!(one.blocks(&two));
+        assert!(two.blocks(&one));
+        let one = TournOp::RecordResult(RoundIdentifier::Number(3), RoundResult::Draw());
+        let two = TournOp::CertifyRound(RoundIdentifier::Number(3), Vec::new());
+        assert!(one.blocks(&two));
+    }
+
+    #[test]
+    fn round_numbers_and_ids_block() {
+        // There is no way to tell if these are the same round, so they can't be reordered
+        let one = TournOp::RecordResult(RoundIdentifier::Number(0), RoundResult::Draw());
+        let two = TournOp::RecordResult(round_id(), RoundResult::Draw());
+        assert!(one.blocks(&two));
+        assert!(two.blocks(&one));
+        let three = TournOp::PauseRound(round_id());
+        let four = TournOp::CallTime(RoundIdentifier::Number(0));
+        assert!(three.blocks(&four));
+    }
+
+    #[test]
+    fn player_names_and_ids_block() {
+        let name = PlayerIdentifier::Name("a".to_owned());
+        let one = TournOp::SetGamerTag(name.clone(), "x".to_owned());
+        let two = TournOp::SetGamerTag(player_id("a"), "y".to_owned());
+        assert!(one.blocks(&two));
+        let three = TournOp::SetGamerTag(PlayerIdentifier::Name("b".to_owned()), "z".to_owned());
+        assert!(!one.blocks(&three));
+        let four = TournOp::SetGamerTag(player_id("c"), "w".to_owned());
+        assert!(!two.blocks(&four));
+    }
+
+    #[test]
+    fn global_ops_block_everything() {
+        let other = TournOp::SetGamerTag(player_id("a"), "x".to_owned());
+        for op in [TournOp::PairRound(), TournOp::Start(), TournOp::Cut(8)] {
+            assert!(op.blocks(&other));
+            assert!(other.blocks(&op));
+        }
+    }
+
+    #[test]
+    fn inactive_ops_never_block() {
+        let mut one = full_op(TournOp::PairRound());
+        let two = full_op(TournOp::PairRound());
+        assert!(one.blocks(&two));
+        one.active = false;
+        assert!(!one.blocks(&two));
+        assert!(!two.blocks(&one));
+    }
+
+    #[test]
+    fn merge_commuting_ops() {
+        let shared = full_op(TournOp::RegisterPlayer("a".to_owned()));
+        let local = full_op(TournOp::RecordResult(round_id(), RoundResult::Draw()));
+        let remote = full_op(TournOp::RecordResult(round_id(), RoundResult::Draw()));
+        let merged = slice(&[&shared, &local])
+            .merge(slice(&[&shared, &remote]))
+            .unwrap();
+        let ids: Vec<OpId> = merged.ops.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![shared.id, remote.id, local.id]);
+    }
+
+    #[test]
+    fn merge_identical_slices() {
+        let one = full_op(TournOp::RegisterPlayer("a".to_owned()));
+        let two = full_op(TournOp::PairRound());
+        let merged = slice(&[&one, &two]).merge(slice(&[&one, &two])).unwrap();
+        let ids: Vec<OpId> = merged.ops.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![one.id, two.id]);
+    }
+
+    #[test]
+    fn merge_blocking_ops() {
+        let shared = full_op(TournOp::RegisterPlayer("a".to_owned()));
+        let local = full_op(TournOp::RecordResult(
+            RoundIdentifier::Number(0),
+            RoundResult::Draw(),
+        ));
+        let remote = full_op(TournOp::RecordResult(round_id(), RoundResult::Draw()));
+        let block = slice(&[&shared, &local])
+            .merge(slice(&[&shared, &remote]))
+            .unwrap_err();
+        assert_eq!(block.problem(), (local.clone(), remote.clone()));
+        assert_eq!(block.agreed.ops, vec![shared]);
+        // Keeping one of the two resolves the sync
+        match block.pick_and_continue(remote.clone()) {
+            SyncStatus::Completed(synced) => {
+                let ids: Vec<OpId> = synced.known.ops.iter().map(|o| o.id).collect();
+                assert!(ids.contains(&remote.id));
+                assert!(!ids.contains(&local.id));
+            }
+            _ => panic!("the sync should be completed"),
+        }
+    }
+
+    #[test]
+    fn drops_block_results() {
+        let shared = full_op(TournOp::RegisterPlayer("a".to_owned()));
+        let plyr = player_id("a");
+        let results = [
+            TournOp::RecordResult(round_id(), RoundResult::Draw()),
+            TournOp::CertifyRound(round_id(), Vec::new()),
+            TournOp::ConfirmResult(plyr.clone()),
+        ];
+        for drop in [
+            TournOp::DropPlayer(plyr.clone()),
+            TournOp::AdminDropPlayer(plyr.clone()),
+        ] {
+            for res in results.iter().cloned() {
+                let local = full_op(drop.clone());
+                let remote = full_op(res);
+                assert_eq!(
+                    conflict(&shared, &local, &remote),
+                    Some((local.clone(), remote.clone()))
+                );
+            }
+        }
+        // Confirmations from other players can still be reordered
+        let local = full_op(TournOp::DropPlayer(plyr));
+        let remote = full_op(TournOp::ConfirmResult(player_id("b")));
+        assert_eq!(conflict(&shared, &local, &remote), None);
+    }
+
+    #[test]
+    fn best_of_blocks_results() {
+        let shared = full_op(TournOp::RegisterPlayer("a".to_owned()));
+        let local = full_op(TournOp::UpdateTournSetting(TournamentSetting::BestOf(3)));
+        for op in [
+            TournOp::RecordResult(round_id(), RoundResult::Draw()),
+            TournOp::CertifyRound(round_id(), Vec::new()),
+        ] {
+            let remote = full_op(op);
+            assert_eq!(
+                conflict(&shared, &local, &remote),
+                Some((local.clone(), remote.clone()))
+            );
+        }
+        // Other settings don't affect results
+        let local = full_op(TournOp::UpdateTournSetting(
+            TournamentSetting::MinDeckCount(1),
+        ));
+        let remote = full_op(TournOp::RecordResult(round_id(), RoundResult::Draw()));
+        assert_eq!(conflict(&shared, &local, &remote), None);
+    }
+
+    #[test]
+    fn registering_staff_blocks_their_ops() {
+        let shared = full_op(TournOp::RegisterPlayer("a".to_owned()));
+        let judge = account();
+        let op = TournOp::RecordResult(round_id(), RoundResult::Draw());
+        for (register, author) in [
+            (
+                TournOp::RegisterJudge(judge.clone()),
+                OpAuthor::Judge(judge.external_id.clone()),
+            ),
+            (
+                TournOp::RegisterAdmin(judge.clone()),
+                OpAuthor::Admin(judge.external_id.clone()),
+            ),
+        ] {
+            let local = full_op(register);
+            let remote = authored(op.clone(), author);
+            assert_eq!(
+                conflict(&shared, &local, &remote),
+                Some((local.clone(), remote.clone()))
+            );
+        }
+        // Ops from other judges don't depend on the registration
+        let local = full_op(TournOp::RegisterJudge(judge));
+        let remote = authored(op, OpAuthor::Judge(account().external_id));
+        assert_eq!(conflict(&shared, &local, &remote), None);
     }
 }
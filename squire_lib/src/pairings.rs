@@ -1,9 +1,182 @@
 use serde::{Deserialize, Serialize};
 
-use crate::swiss_pairings::PlayerId;
+use crate::{
+    matching::{max_weight_matching, Edge},
+    round_registry::RoundRegistry,
+    swiss_pairings::PlayerId,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Pairings {
     pub paired: Vec<Vec<PlayerId>>,
     pub rejected: Vec<PlayerId>,
 }
+
+/// Splits the given number of players into the sizes of the pods that they should be paired
+/// into. Every pod has between `min` and `max` players (inclusive), as few pods as possible are
+/// used, and their sizes are kept as even as possible, e.g. 13 players with pods of 3 to 4
+/// players are split into pods of 4, 3, 3, and 3. Larger pods come first.
+///
+/// If the players can't all be split into pods, as many players as possible are placed into
+/// pods. The sizes will then sum to less than the number of players.
+pub(crate) fn pod_sizes(count: usize, min: u8, max: u8) -> Vec<usize> {
+    let max = max as usize;
+    if max == 0 {
+        return Vec::new();
+    }
+    // A "pod" of one player is a bye, not a match
+    let min = (min as usize).clamp(max.min(2), max);
+    for total in (1..=count).rev() {
+        let pods = total.div_ceil(max);
+        if pods * min <= total {
+            let base = total / pods;
+            let extra = total % pods;
+            return (0..pods)
+                .map(|i| if i < extra { base + 1 } else { base })
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+/// Orders the players in a pod so that each player's seat positions are balanced over the course
+/// of the event. Every player is put into the seat that they have sat in the fewest times, where
+/// possible. Seats are assigned using a minimum cost assignment between players and seats.
+pub(crate) fn balance_seats(pod: Vec<PlayerId>, matches: &RoundRegistry) -> Vec<PlayerId> {
+    let size = pod.len();
+    if size < 2 {
+        return pod;
+    }
+    let costs: Vec<Vec<i64>> = pod
+        .iter()
+        .map(|p| {
+            let mut counts = vec![0; size];
+            for seat in matches.get_seat_history(p) {
+                if seat < size {
+                    counts[seat] += 1;
+                }
+            }
+            counts
+        })
+        .collect();
+    let max_cost = costs.iter().flatten().copied().max().unwrap_or_default();
+    // Players are vertices 0..size and seats are vertices size..2 * size
+    let edges: Vec<Edge> = costs
+        .iter()
+        .enumerate()
+        .flat_map(|(plyr, counts)| {
+            counts
+                .iter()
+                .enumerate()
+                .map(move |(seat, c)| (plyr, size + seat, max_cost + 1 - c))
+        })
+        .collect();
+    let mut seats: Vec<Option<PlayerId>> = vec![None; size];
+    for (plyr, seat) in max_weight_matching(&edges, true)
+        .into_iter()
+        .take(size)
+        .enumerate()
+    {
+        if let Some(seat) = seat {
+            seats[seat - size] = Some(pod[plyr].clone());
+        }
+    }
+    seats.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{operations::OpContext, player::Player};
+
+    fn players(count: usize) -> Vec<PlayerId> {
+        (0..count)
+            .map(|i| Player::new(format!("Player {i}")).id)
+            .collect()
+    }
+
+    /// Creates a registry where each of the given pods played a match, seated in the given order
+    fn registry(pods: &[Vec<PlayerId>]) -> RoundRegistry {
+        let mut reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        for pod in pods {
+            let ident = reg.create_round(&mut ctx);
+            for plyr in pod {
+                reg.add_player_to_round(&ident, plyr.clone()).unwrap();
+            }
+        }
+        reg
+    }
+
+    #[test]
+    fn pods_are_as_even_as_possible() {
+        assert_eq!(pod_sizes(13, 3, 4), vec![4, 3, 3, 3]);
+        assert_eq!(pod_sizes(7, 3, 4), vec![4, 3]);
+        assert_eq!(pod_sizes(10, 3, 4), vec![4, 3, 3]);
+        assert_eq!(pod_sizes(8, 4, 4), vec![4, 4]);
+        assert_eq!(pod_sizes(6, 2, 2), vec![2, 2, 2]);
+    }
+
+    #[test]
+    fn leftover_players_are_not_podded() {
+        // Without smaller pods, two players are left over
+        assert_eq!(pod_sizes(10, 4, 4), vec![4, 4]);
+        // Five players can't be split into pods of three or four
+        assert_eq!(pod_sizes(5, 3, 4), vec![4]);
+        assert_eq!(pod_sizes(2, 3, 4), Vec::<usize>::new());
+        assert_eq!(pod_sizes(7, 2, 2), vec![2, 2, 2]);
+    }
+
+    #[test]
+    fn pods_have_at_least_two_players() {
+        assert_eq!(pod_sizes(5, 1, 4), vec![3, 2]);
+        assert_eq!(pod_sizes(5, 0, 4), vec![3, 2]);
+        assert_eq!(pod_sizes(1, 1, 4), Vec::<usize>::new());
+        assert_eq!(pod_sizes(5, 2, 0), Vec::<usize>::new());
+        // A minimum above the maximum is treated as the maximum
+        assert_eq!(pod_sizes(9, 5, 4), vec![4, 4]);
+    }
+
+    #[test]
+    fn players_swap_seats() {
+        let plyrs = players(2);
+        let reg = registry(std::slice::from_ref(&plyrs));
+        let seats = balance_seats(plyrs.clone(), &reg);
+        assert_eq!(seats, vec![plyrs[1].clone(), plyrs[0].clone()]);
+    }
+
+    #[test]
+    fn players_avoid_their_most_common_seats() {
+        let plyrs = players(4);
+        let reg = registry(&[
+            plyrs.clone(),
+            vec![
+                plyrs[1].clone(),
+                plyrs[0].clone(),
+                plyrs[3].clone(),
+                plyrs[2].clone(),
+            ],
+        ]);
+        let seats = balance_seats(plyrs.clone(), &reg);
+        assert_eq!(seats.len(), 4);
+        for (seat, plyr) in seats.iter().enumerate() {
+            assert!(plyrs.contains(plyr));
+            assert!(!reg.get_seat_history(plyr).contains(&seat));
+        }
+    }
+
+    #[test]
+    fn new_players_fill_the_remaining_seats() {
+        let plyrs = players(3);
+        // Only the first player has played before, and they sat first
+        let reg = registry(&[vec![plyrs[0].clone(), players(1).remove(0)]]);
+        let seats = balance_seats(plyrs.clone(), &reg);
+        assert_eq!(seats.len(), 3);
+        assert_ne!(seats[0], plyrs[0]);
+        assert!(plyrs.iter().all(|p| seats.contains(p)));
+    }
+}
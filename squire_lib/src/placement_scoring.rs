@@ -0,0 +1,354 @@
+pub use crate::{
+    player::PlayerId,
+    player_registry::PlayerRegistry,
+    round::Round,
+    round_registry::RoundRegistry,
+    scoring::{Score, Standings},
+    settings::PlacementScoringSetting,
+};
+
+use crate::scoring::{seeded_draw, CountedRound, RoundCache};
+
+use serde::{Deserialize, Serialize};
+
+use std::{collections::HashMap, fmt::Display};
+
+/// A score for multiplayer pods, where players earn points based on where they finish
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct PlacementScore {
+    pub points: f64,
+    /// The points earned as a fraction of the points for finishing first in every match. Byes
+    /// are left out.
+    pub point_percent: f64,
+    /// The average point percentage of every opponent. Each pod counts all of its opponents.
+    pub opp_point_percent: f64,
+    /// The average place that the player finished in, starting from 1. Byes are left out.
+    pub avg_placement: f64,
+    /// The player's place in the standings, starting from 1
+    pub rank: usize,
+}
+
+/// A scoring system where each match is scored by its finishing order, e.g. 1st through 4th in
+/// a Commander pod. Players that tie for a place split the points for the places that they
+/// cover.
+///
+/// If a match has no recorded finishing order, its winner is placed first and everyone else ties
+/// for second. Without a winner, everyone ties for first.
+///
+/// Players are ranked by points, then opponent point percentage, then point percentage, and then
+/// average placement. Any remaining ties are broken by a random draw that is the same every time
+/// the standings are calculated.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlacementScoring {
+    placement_points: Vec<f64>,
+    bye_points: f64,
+    /// The seed for the random draw that breaks ties that remain after every tiebreaker
+    #[serde(default)]
+    tiebreak_seed: u64,
+    /// This isn't serialized. Until it is rebuilt, the rounds are counted from scratch.
+    #[serde(skip)]
+    cache: Option<RoundCache>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PlacementCounter {
+    points: f64,
+    bye_points: f64,
+    matches: u64,
+    placements: f64,
+    opponents: Vec<PlayerId>,
+}
+
+impl PlacementScoring {
+    pub fn new() -> Self {
+        PlacementScoring {
+            placement_points: vec![3.0, 2.0, 1.0, 0.0],
+            bye_points: 3.0,
+            tiebreak_seed: 0,
+            cache: None,
+        }
+    }
+
+    pub fn update_setting(&mut self, setting: PlacementScoringSetting) {
+        use PlacementScoringSetting::*;
+        match setting {
+            PlacementPoints(p) => {
+                self.placement_points = p;
+            }
+            ByePoints(p) => {
+                self.bye_points = p;
+            }
+            TiebreakSeed(seed) => {
+                self.tiebreak_seed = seed;
+            }
+        }
+    }
+
+    /// The points for finishing in the given place, starting from 0. Places past the end of the
+    /// list are worth nothing.
+    fn points_for(&self, place: usize) -> f64 {
+        self.placement_points
+            .get(place)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Calculates the points and average place (starting from 1) for everyone in a round
+    fn score_round(&self, round: &CountedRound) -> HashMap<PlayerId, (f64, f64)> {
+        let places: Vec<Vec<PlayerId>> = match (round.placements(), &round.winner) {
+            (Some(places), _) => places.clone(),
+            (None, Some(winner)) => vec![
+                vec![winner.clone()],
+                round
+                    .players
+                    .iter()
+                    .filter(|p| *p != winner)
+                    .cloned()
+                    .collect(),
+            ],
+            (None, None) => vec![round.players.clone()],
+        };
+        let mut digest = HashMap::with_capacity(round.players.len());
+        let mut place = 0;
+        for group in places.into_iter().filter(|g| !g.is_empty()) {
+            let size = group.len();
+            let points = (place..place + size)
+                .map(|i| self.points_for(i))
+                .sum::<f64>();
+            let points = points / (size as f64);
+            let avg_place = (place as f64) + (size as f64 + 1.0) / 2.0;
+            for plyr in group {
+                digest.insert(plyr, (points, avg_place));
+            }
+            place += size;
+        }
+        digest
+    }
+
+    fn point_percent(&self, counter: &PlacementCounter) -> Option<f64> {
+        let most = self.points_for(0) * (counter.matches as f64);
+        (most > 0.0).then(|| counter.points / most)
+    }
+
+    /// Updates the counted rounds after a round is certified, killed, or has its results changed
+    pub fn update_round(&mut self, round_reg: &RoundRegistry, round: &Round, first_match: u64) {
+        RoundCache::update(&mut self.cache, round_reg, round, first_match);
+    }
+
+    /// Checks the counted rounds against a full recount
+    pub fn verify_cache(&self, round_reg: &RoundRegistry, first_match: u64) -> bool {
+        RoundCache::verify(&self.cache, round_reg, first_match)
+    }
+
+    /// Calculates the standings from the rounds from `first_match` onwards
+    pub fn get_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        first_match: u64,
+    ) -> Standings<PlacementScore> {
+        // Dropped players are tallied too as they still count towards their opponents' scores
+        let mut counters: HashMap<PlayerId, PlacementCounter> = player_reg
+            .players
+            .keys()
+            .map(|id| (id.clone(), PlacementCounter::default()))
+            .collect();
+        let rounds = RoundCache::get_or_tally(&self.cache, round_reg, first_match);
+        for round in rounds.rounds() {
+            if round.is_bye {
+                for plyr in &round.players {
+                    if let Some(counter) = counters.get_mut(plyr) {
+                        counter.bye_points += self.bye_points;
+                    }
+                }
+                continue;
+            }
+            for (plyr, (points, place)) in self.score_round(round) {
+                if let Some(counter) = counters.get_mut(&plyr) {
+                    counter.points += points;
+                    counter.placements += place;
+                    counter.matches += 1;
+                    counter
+                        .opponents
+                        .extend(round.players.iter().filter(|p| **p != plyr).cloned());
+                }
+            }
+        }
+        let mut results: Vec<(PlayerId, PlacementScore)> = counters
+            .iter()
+            .filter(|(id, _)| {
+                player_reg
+                    .players
+                    .get(id)
+                    .map(|p| p.can_play())
+                    .unwrap_or_default()
+            })
+            .map(|(id, counter)| {
+                let mut opps: Vec<f64> = counter
+                    .opponents
+                    .iter()
+                    .filter_map(|o| counters.get(o))
+                    .filter_map(|c| self.point_percent(c))
+                    .collect();
+                // Rounds are visited in map order, so sort before summing to get the same total
+                opps.sort_by(|a, b| a.total_cmp(b));
+                let score = PlacementScore {
+                    points: counter.points + counter.bye_points,
+                    point_percent: self.point_percent(counter).unwrap_or_default(),
+                    opp_point_percent: match opps.len() {
+                        0 => 0.0,
+                        n => opps.iter().sum::<f64>() / (n as f64),
+                    },
+                    avg_placement: match counter.matches {
+                        0 => 0.0,
+                        n => counter.placements / (n as f64),
+                    },
+                    rank: 0,
+                };
+                (id.clone(), score)
+            })
+            .collect();
+        results.sort_by(|(a_id, a), (b_id, b)| {
+            a.points
+                .total_cmp(&b.points)
+                .then(a.opp_point_percent.total_cmp(&b.opp_point_percent))
+                .then(a.point_percent.total_cmp(&b.point_percent))
+                // A lower average placement is better
+                .then(b.avg_placement.total_cmp(&a.avg_placement))
+                .then_with(|| {
+                    seeded_draw(self.tiebreak_seed, a_id)
+                        .cmp(&seeded_draw(self.tiebreak_seed, b_id))
+                })
+        });
+        // Standings go from last place to first
+        let count = results.len();
+        for (i, (_, score)) in results.iter_mut().enumerate() {
+            score.rank = count - i;
+        }
+        Standings::new(results)
+    }
+}
+
+impl Score for PlacementScore {
+    fn primary_score(&self) -> f64 {
+        self.points
+    }
+}
+
+impl Display for PlacementScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PlacementScore {{ points: {}, point percent: {:3}, opponent point percent: {:3}, average placement: {:2} }}",
+            self.points, self.point_percent, self.opp_point_percent, self.avg_placement
+        )
+    }
+}
+
+impl Default for PlacementScoring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{operations::OpContext, player::Player, round::RoundResult};
+
+    /// Scores a single pod of four players with the results made by the given function
+    fn score_pod<F>(sys: &PlacementScoring, results: F) -> Vec<PlacementScore>
+    where
+        F: Fn(&[PlayerId]) -> Vec<RoundResult>,
+    {
+        let mut plyr_reg = PlayerRegistry::new();
+        let plyrs: Vec<PlayerId> = ["A", "B", "C", "D"]
+            .into_iter()
+            .map(|name| {
+                let plyr = Player::new(name.into());
+                let id = plyr.id.clone();
+                plyr_reg.import_player(plyr).unwrap();
+                id
+            })
+            .collect();
+        let mut rnd_reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let ident = rnd_reg.create_round(&mut OpContext::new(Uuid::new_v4(), SystemTime::now()));
+        for p in &plyrs {
+            rnd_reg.add_player_to_round(&ident, p.clone()).unwrap();
+        }
+        rnd_reg
+            .get_mut_round(&ident)
+            .unwrap()
+            .force_certify(results(&plyrs))
+            .unwrap();
+        let standings = sys.get_standings(&plyr_reg, &rnd_reg, 0);
+        plyrs
+            .iter()
+            .map(|p| {
+                standings
+                    .scores
+                    .iter()
+                    .find(|(id, _)| id == p)
+                    .unwrap()
+                    .1
+                    .clone()
+            })
+            .collect()
+    }
+
+    fn points(scores: &[PlacementScore]) -> Vec<f64> {
+        scores.iter().map(|s| s.points).collect()
+    }
+
+    fn avg_placements(scores: &[PlacementScore]) -> Vec<f64> {
+        scores.iter().map(|s| s.avg_placement).collect()
+    }
+
+    #[test]
+    fn tied_places_split_their_points() {
+        let scores = score_pod(&PlacementScoring::new(), |p| {
+            vec![RoundResult::Placements(vec![
+                vec![p[0].clone()],
+                vec![p[1].clone(), p[2].clone()],
+                vec![p[3].clone()],
+            ])]
+        });
+        // The tied players split the points for 2nd and 3rd
+        assert_eq!(points(&scores), vec![3.0, 1.5, 1.5, 0.0]);
+        assert_eq!(avg_placements(&scores), vec![1.0, 2.5, 2.5, 4.0]);
+        assert_eq!(scores[1].point_percent, 0.5);
+        assert_eq!(scores[0].rank, 1);
+        assert_eq!(scores[3].rank, 4);
+    }
+
+    #[test]
+    fn results_without_placements() {
+        let sys = PlacementScoring::new();
+        // Everyone but the winner ties for 2nd
+        let scores = score_pod(&sys, |p| vec![RoundResult::Wins(p[2].clone(), 1)]);
+        assert_eq!(points(&scores), vec![1.0, 1.0, 3.0, 1.0]);
+        assert_eq!(avg_placements(&scores), vec![3.0, 3.0, 1.0, 3.0]);
+        // Without a winner, everyone ties for 1st
+        let scores = score_pod(&sys, |_| vec![RoundResult::Draw()]);
+        assert_eq!(points(&scores), vec![1.5; 4]);
+        assert_eq!(avg_placements(&scores), vec![2.5; 4]);
+    }
+
+    #[test]
+    fn places_past_the_points_list_are_worth_nothing() {
+        let mut sys = PlacementScoring::new();
+        sys.update_setting(PlacementScoringSetting::PlacementPoints(vec![5.0, 3.0]));
+        let scores = score_pod(&sys, |p| {
+            vec![RoundResult::Placements(vec![
+                vec![p[0].clone()],
+                vec![p[1].clone(), p[2].clone(), p[3].clone()],
+            ])]
+        });
+        assert_eq!(points(&scores), vec![5.0, 1.0, 1.0, 1.0]);
+    }
+}
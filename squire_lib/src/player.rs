@@ -1,4 +1,4 @@
-use crate::error::TournamentError;
+use crate::{accounts::AccountId, error::TournamentError};
 
 use mtgjson::model::deck::Deck;
 use uuid::Uuid;
@@ -18,7 +18,7 @@ pub enum PlayerStatus {
     Dropped,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(C)]
 pub struct PlayerId(Uuid);
 
@@ -30,19 +30,27 @@ pub struct Player {
     pub deck_ordering: Vec<String>,
     pub decks: HashMap<String, Deck>,
     pub status: PlayerStatus,
+    /// The account that the player registered with, if any
+    #[serde(default)]
+    pub account: Option<AccountId>,
 }
 
 impl Eq for Player {}
 
 impl Player {
     pub fn new(name: String) -> Self {
+        Self::with_id(Uuid::new_v4(), name)
+    }
+
+    pub(crate) fn with_id(id: Uuid, name: String) -> Self {
         Player {
-            id: PlayerId(Uuid::new_v4()),
+            id: PlayerId(id),
             name,
             game_name: None,
             deck_ordering: Vec::new(),
             decks: HashMap::new(),
             status: PlayerStatus::Registered,
+            account: None,
         }
     }
 
@@ -1,6 +1,9 @@
 use crate::{
+    accounts::{AccountId, UserAccount},
     error::TournamentError,
+    operations::OpContext,
     player::{Player, PlayerId, PlayerStatus},
+    team::Team,
 };
 
 use cycle_map::CycleMap;
@@ -24,6 +27,9 @@ pub struct PlayerRegistry {
     pub name_and_id: CycleMap<String, PlayerId>,
     pub players: HashMap<PlayerId, Player>,
     pub(crate) check_ins: HashSet<PlayerId>,
+    /// The members of the teams in the registry, keyed by the id of the team
+    #[serde(default)]
+    pub teams: HashMap<PlayerId, Team>,
 }
 
 impl Default for PlayerRegistry {
@@ -38,6 +44,7 @@ impl PlayerRegistry {
             name_and_id: CycleMap::new(),
             players: HashMap::new(),
             check_ins: HashSet::new(),
+            teams: HashMap::new(),
         }
     }
 
@@ -78,11 +85,15 @@ impl PlayerRegistry {
         }
     }
 
-    pub fn add_player(&mut self, name: String) -> Result<PlayerId, TournamentError> {
+    pub fn add_player(
+        &mut self,
+        name: String,
+        ctx: &mut OpContext,
+    ) -> Result<PlayerId, TournamentError> {
         if self.verify_identifier(&PlayerIdentifier::Name(name.clone())) {
             Err(TournamentError::PlayerLookup)
         } else {
-            let plyr = Player::new(name.clone());
+            let plyr = Player::with_id(ctx.next_id(), name.clone());
             let digest = Ok(plyr.id.clone());
             self.name_and_id.insert(name, plyr.id.clone());
             self.players.insert(plyr.id.clone(), plyr);
@@ -90,6 +101,66 @@ impl PlayerRegistry {
         }
     }
 
+    /// Adds a player that is linked to the given account and named after it. Each account can only
+    /// be linked to one player.
+    pub fn add_account_player(
+        &mut self,
+        account: &UserAccount,
+        ctx: &mut OpContext,
+    ) -> Result<PlayerId, TournamentError> {
+        if self.get_account_player(&account.external_id).is_some() {
+            return Err(TournamentError::PlayerLookup);
+        }
+        let id = self.add_player(account.account_name.clone(), ctx)?;
+        if let Some(plyr) = self.players.get_mut(&id) {
+            plyr.account = Some(account.external_id.clone());
+        }
+        Ok(id)
+    }
+
+    /// Gets the id of the player that is linked to the given account
+    pub fn get_account_player(&self, account: &AccountId) -> Option<PlayerId> {
+        self.players
+            .values()
+            .find(|p| p.account.as_ref() == Some(account))
+            .map(|p| p.id.clone())
+    }
+
+    /// Adds a team with the given members. The team is added as a player so that it can be
+    /// paired and scored like one.
+    pub fn add_team(
+        &mut self,
+        name: String,
+        members: Vec<String>,
+        ctx: &mut OpContext,
+    ) -> Result<PlayerId, TournamentError> {
+        if members
+            .iter()
+            .enumerate()
+            .any(|(i, m)| members[..i].contains(m))
+        {
+            return Err(TournamentError::PlayerLookup);
+        }
+        let id = self.add_player(name, ctx)?;
+        self.teams
+            .insert(id.clone(), Team::new(id.clone(), members, ctx));
+        Ok(id)
+    }
+
+    pub fn get_team(&self, ident: &PlayerIdentifier) -> Option<&Team> {
+        let id = self.get_player_id(ident)?;
+        self.teams.get(&id)
+    }
+
+    pub fn get_mut_team(&mut self, ident: &PlayerIdentifier) -> Option<&mut Team> {
+        let id = self.get_player_id(ident)?;
+        self.teams.get_mut(&id)
+    }
+
+    pub fn is_team(&self, id: &PlayerId) -> bool {
+        self.teams.contains_key(id)
+    }
+
     pub fn drop_player(&mut self, ident: &PlayerIdentifier) -> Option<()> {
         let plyr = self.get_mut_player(ident)?;
         plyr.update_status(PlayerStatus::Dropped);
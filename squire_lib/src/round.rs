@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt,
     hash::{Hash, Hasher},
     time::{Duration, Instant, SystemTime},
@@ -16,10 +16,14 @@ use crate::{error::TournamentError, player::PlayerId};
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
 #[repr(C)]
 pub enum RoundStatus {
+    /// No result has been reported yet
     Open,
+    /// A result has been reported and is waiting for everyone to confirm it
     Uncertified,
     Certified,
     Dead,
+    /// A player disagreed with the reported result, so a judge needs to certify the round
+    Disputed,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
@@ -27,18 +31,30 @@ pub enum RoundStatus {
 pub enum RoundResult {
     Wins(PlayerId, u8),
     Draw(),
+    /// The finishing order of a pod, from first place to last. Each entry holds the players that
+    /// tied for that place.
+    Placements(Vec<Vec<PlayerId>>),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
 #[repr(C)]
 pub struct RoundId(Uuid);
 
+/// The extra turns that are played after time is called in a round
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct ExtraTurns {
+    pub allowed: u8,
+    pub taken: u8,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Round {
     pub id: RoundId,
     pub match_number: u64,
     pub table_number: u64,
-    pub players: HashSet<PlayerId>,
+    /// The players in the round, in seat order
+    pub players: Vec<PlayerId>,
     pub(crate) confirmations: HashSet<PlayerId>,
     pub(crate) results: Vec<RoundResult>,
     pub status: RoundStatus,
@@ -47,81 +63,314 @@ pub struct Round {
     pub(crate) length: Duration,
     pub(crate) extension: Duration,
     pub(crate) is_bye: bool,
+    /// When the clock was paused, if it is paused
+    #[serde(default)]
+    pub(crate) paused_at: Option<SystemTime>,
+    /// The total time that the clock has been paused for, not counting an ongoing pause
+    #[serde(default)]
+    pub(crate) time_paused: Duration,
+    /// The extra turns for the round. This is `None` until time is called.
+    #[serde(default)]
+    pub time_called: Option<ExtraTurns>,
+    /// The most games that can be played in the match, e.g. 3 for a best-of-three. A value of 0
+    /// puts no limit on the results that can be recorded.
+    #[serde(default)]
+    pub best_of: u8,
 }
 
 impl Round {
     pub fn new(match_num: u64, table_number: u64, len: Duration) -> Self {
+        Self::with_id(
+            Uuid::new_v4(),
+            SystemTime::now(),
+            match_num,
+            table_number,
+            len,
+        )
+    }
+
+    /// Creates a round with the given id whose clock starts at the given time
+    pub(crate) fn with_id(
+        id: Uuid,
+        start: SystemTime,
+        match_num: u64,
+        table_number: u64,
+        len: Duration,
+    ) -> Self {
         Round {
-            id: RoundId(Uuid::new_v4()),
+            id: RoundId(id),
             match_number: match_num,
             table_number,
-            players: HashSet::with_capacity(4),
+            players: Vec::with_capacity(4),
             confirmations: HashSet::with_capacity(4),
             results: Vec::with_capacity(3),
             status: RoundStatus::Open,
             winner: None,
-            timer: SystemTime::now(),
+            timer: start,
             length: len,
             extension: Duration::from_secs(0),
             is_bye: false,
+            paused_at: None,
+            time_paused: Duration::from_secs(0),
+            time_called: None,
+            best_of: 0,
         }
     }
 
-    // TODO: Find a better way to sync clocks if SystemTime::elapsed errors
+    // TODO: Find a better way to sync clocks if SystemTime::duration_since errors
     pub fn time_left(&self) -> Duration {
         let length = self.length + self.extension;
-        let elapsed = match self.timer.elapsed() {
-            Ok(e) => e,
+        // The clock doesn't run while the round is paused
+        let now = self.paused_at.unwrap_or_else(SystemTime::now);
+        let elapsed = match now.duration_since(self.timer) {
+            Ok(e) => e.saturating_sub(self.time_paused),
             Err(_) => {
                 return Duration::from_secs(0);
             }
         };
-        if elapsed > length {
-            Duration::from_secs(0)
+        length.saturating_sub(elapsed)
+    }
+
+    /// Checks if the round is still being played, i.e. it hasn't been certified or killed
+    pub fn is_active(&self) -> bool {
+        matches!(self.status, RoundStatus::Open | RoundStatus::Uncertified)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Checks if the round has run out of time but time hasn't been called yet
+    pub fn is_expired(&self) -> bool {
+        self.is_active()
+            && !self.is_paused()
+            && self.time_called.is_none()
+            && self.time_left() == Duration::from_secs(0)
+    }
+
+    /// Stops the round's clock at the given time, e.g. for a deck check
+    pub fn pause(&mut self, now: SystemTime) -> Result<(), TournamentError> {
+        if !self.is_active() {
+            Err(TournamentError::NoActiveRound)
+        } else if self.is_paused() {
+            Err(TournamentError::ClockPaused)
         } else {
-            length - elapsed
+            self.paused_at = Some(now);
+            Ok(())
+        }
+    }
+
+    /// Restarts the round's clock at the given time after it was paused
+    pub fn resume(&mut self, now: SystemTime) -> Result<(), TournamentError> {
+        if !self.is_active() {
+            return Err(TournamentError::NoActiveRound);
+        }
+        let paused_at = self.paused_at.take().ok_or(TournamentError::ClockRunning)?;
+        self.time_paused += now.duration_since(paused_at).unwrap_or_default();
+        Ok(())
+    }
+
+    /// Ends the round's clock at the given time. The players then get the given number of extra
+    /// turns.
+    pub fn call_time(&mut self, now: SystemTime, extra_turns: u8) -> Result<(), TournamentError> {
+        if !self.is_active() {
+            return Err(TournamentError::NoActiveRound);
+        }
+        if self.time_called.is_some() {
+            return Err(TournamentError::TimeCalled);
+        }
+        if self.is_paused() {
+            self.resume(now)?;
+        }
+        self.time_called = Some(ExtraTurns {
+            allowed: extra_turns,
+            taken: 0,
+        });
+        Ok(())
+    }
+
+    /// Marks one of the round's extra turns as played
+    pub fn take_extra_turn(&mut self) -> Result<(), TournamentError> {
+        if !self.is_active() {
+            return Err(TournamentError::NoActiveRound);
         }
+        let turns = self
+            .time_called
+            .as_mut()
+            .ok_or(TournamentError::TimeNotCalled)?;
+        if turns.taken >= turns.allowed {
+            return Err(TournamentError::NoExtraTurns);
+        }
+        turns.taken += 1;
+        Ok(())
     }
 
     pub fn get_id(&self) -> RoundId {
         self.id.clone()
     }
 
+    /// Adds a player to the round in the next open seat
     pub fn add_player(&mut self, player: PlayerId) {
-        self.players.insert(player);
+        if !self.players.contains(&player) {
+            self.players.push(player);
+        }
     }
 
     pub fn get_all_players(&self) -> HashSet<PlayerId> {
-        self.players.clone()
+        self.players.iter().cloned().collect()
     }
 
-    fn verify_result(&self, result: &RoundResult) -> bool {
-        match result {
+    /// Gets the seat (starting from 0) that the player is in
+    pub fn get_seat(&self, id: &PlayerId) -> Option<usize> {
+        self.players.iter().position(|p| p == id)
+    }
+
+    /// Checks that a full set of results is valid for the round
+    fn verify_results(&self, results: &[RoundResult]) -> Result<(), TournamentError> {
+        let in_round = results.iter().all(|result| match result {
             RoundResult::Wins(p_id, _) => self.players.contains(p_id),
             RoundResult::Draw() => true,
+            RoundResult::Placements(places) => {
+                let plyrs: Vec<&PlayerId> = places.iter().flatten().collect();
+                places.iter().all(|group| !group.is_empty())
+                    && plyrs.len() == self.players.len()
+                    && self.players.iter().all(|p| plyrs.contains(&p))
+            }
+        });
+        if !in_round {
+            return Err(TournamentError::PlayerNotInRound);
         }
+        if self.best_of == 0 {
+            return Ok(());
+        }
+        // Results are totals rather than single games, so they can come in any order. The match
+        // is over once a player has won a majority of the games, so no one can win more.
+        let needed = (self.best_of / 2 + 1) as u64;
+        let (wins, games) = count_games(results);
+        if games > self.best_of as u64 || wins.values().any(|w| *w > needed) {
+            return Err(TournamentError::InvalidResult);
+        }
+        Ok(())
     }
 
+    /// Works out who won the match from the recorded results. The player with the most game wins
+    /// takes the match, and there is no winner if that is tied. A bye is won by its player.
+    pub fn match_winner(&self) -> Option<PlayerId> {
+        if self.is_bye {
+            return self.players.first().cloned();
+        }
+        let (wins, _) = count_games(&self.results);
+        let most = *wins.values().max()?;
+        let mut leaders = wins.into_iter().filter(|(_, w)| *w == most);
+        match (leaders.next(), leaders.next()) {
+            (Some((p, _)), None) => Some(p.clone()),
+            _ => None,
+        }
+    }
+
+    /// Gets the most recently recorded finishing order of the round, if any
+    pub fn get_placements(&self) -> Option<&Vec<Vec<PlayerId>>> {
+        last_placements(&self.results)
+    }
+
+    /// Records a result for the round. A player's game wins replace their earlier total and a
+    /// finishing order replaces the earlier one, so a result can be corrected or sent again. Each
+    /// draw is another drawn game.
     pub fn record_result(&mut self, result: RoundResult) -> Result<(), TournamentError> {
-        if self.verify_result(&result) {
-            self.results.push(result);
-            Ok(())
+        let mut results: Vec<RoundResult> = self
+            .results
+            .iter()
+            .filter(|r| !result.replaces(r))
+            .cloned()
+            .collect();
+        results.push(result);
+        self.verify_results(&results)?;
+        self.results = results;
+        // A result recorded after certification corrects the outcome of the match without another
+        // round of confirmations. Judges can replace every result at once with `force_certify`.
+        if self.is_certified() {
+            self.winner = self.match_winner();
         } else {
-            Err(TournamentError::PlayerNotInRound)
+            // Everyone needs to confirm the new result, even if they confirmed an earlier one
+            self.confirmations.clear();
+            if self.status == RoundStatus::Open {
+                self.status = RoundStatus::Uncertified;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the round has a reported result that players can respond to
+    fn check_awaiting_confirmation(&self, player: &PlayerId) -> Result<(), TournamentError> {
+        if !self.players.contains(player) {
+            return Err(TournamentError::PlayerNotInRound);
+        }
+        match self.status {
+            RoundStatus::Uncertified => Ok(()),
+            RoundStatus::Open => Err(TournamentError::NoResult),
+            RoundStatus::Disputed => Err(TournamentError::RoundDisputed),
+            RoundStatus::Certified | RoundStatus::Dead => Err(TournamentError::NoActiveRound),
         }
     }
 
+    /// Marks that the player agrees with the reported result. The round is certified once
+    /// everyone in it has confirmed.
     pub fn confirm_round(&mut self, player: PlayerId) -> Result<RoundStatus, TournamentError> {
-        if !self.players.contains(&player) {
-            Err(TournamentError::PlayerNotInRound)
-        } else {
-            self.confirmations.insert(player);
-            if self.confirmations.len() == self.players.len() {
-                Ok(RoundStatus::Certified)
-            } else {
-                Ok(RoundStatus::Uncertified)
-            }
+        self.check_awaiting_confirmation(&player)?;
+        self.confirmations.insert(player);
+        if self.confirmations.len() == self.players.len() {
+            self.certify();
         }
+        Ok(self.status)
+    }
+
+    /// Marks that the player disagrees with the reported result. The round stays disputed until
+    /// a judge certifies it.
+    pub fn dispute_round(&mut self, player: PlayerId) -> Result<(), TournamentError> {
+        self.check_awaiting_confirmation(&player)?;
+        self.confirmations.clear();
+        self.status = RoundStatus::Disputed;
+        Ok(())
+    }
+
+    /// Replaces the round's results with the given ones and certifies the round without waiting
+    /// for confirmations. The round is left unchanged if any of the results are invalid.
+    pub fn force_certify(&mut self, results: Vec<RoundResult>) -> Result<(), TournamentError> {
+        if self.status == RoundStatus::Dead {
+            return Err(TournamentError::NoActiveRound);
+        }
+        if self.is_bye {
+            return Err(TournamentError::InvalidBye);
+        }
+        self.verify_results(&results)?;
+        self.results = results;
+        self.certify();
+        Ok(())
+    }
+
+    /// Certifies the round as a loss for the given player, e.g. because they were dropped
+    /// mid-match. Every other player finishes ahead of them.
+    pub fn forfeit(&mut self, player: &PlayerId) -> Result<(), TournamentError> {
+        if !self.players.contains(player) {
+            return Err(TournamentError::PlayerNotInRound);
+        }
+        let others: Vec<PlayerId> = self
+            .players
+            .iter()
+            .filter(|p| *p != player)
+            .cloned()
+            .collect();
+        let result = match others.as_slice() {
+            [] => return Err(TournamentError::InvalidBye),
+            [opp] => RoundResult::Wins(opp.clone(), self.best_of / 2 + 1),
+            _ => RoundResult::Placements(vec![others, vec![player.clone()]]),
+        };
+        self.force_certify(vec![result])
+    }
+
+    fn certify(&mut self) {
+        self.status = RoundStatus::Certified;
+        self.winner = self.match_winner();
     }
 
     pub fn kill_round(&mut self) {
@@ -133,7 +382,7 @@ impl Round {
             Err(TournamentError::InvalidBye)
         } else {
             self.is_bye = true;
-            self.winner = Some(self.players.iter().next().unwrap().clone());
+            self.winner = Some(self.players[0].clone());
             self.status = RoundStatus::Certified;
             Ok(())
         }
@@ -141,12 +390,56 @@ impl Round {
 
     pub fn clear_results(&mut self) {
         self.results.clear();
+        self.confirmations.clear();
+        if !self.is_bye {
+            self.winner = None;
+        }
+        if self.status == RoundStatus::Uncertified {
+            self.status = RoundStatus::Open;
+        }
     }
 
     pub fn is_certified(&self) -> bool {
         self.status == RoundStatus::Certified
     }
 }
+
+/// Finds the most recently recorded finishing order in the given results, if any
+pub(crate) fn last_placements(results: &[RoundResult]) -> Option<&Vec<Vec<PlayerId>>> {
+    results.iter().rev().find_map(|r| match r {
+        RoundResult::Placements(places) => Some(places),
+        _ => None,
+    })
+}
+
+/// Counts the games won by each player and the total number of games played. A finishing order
+/// is a single game that is won by whoever finished first alone.
+fn count_games<'a, I>(results: I) -> (HashMap<&'a PlayerId, u64>, u64)
+where
+    I: IntoIterator<Item = &'a RoundResult>,
+{
+    let mut wins: HashMap<&PlayerId, u64> = HashMap::new();
+    let mut games = 0;
+    for result in results {
+        match result {
+            RoundResult::Wins(p, count) => {
+                *wins.entry(p).or_default() += *count as u64;
+                games += *count as u64;
+            }
+            RoundResult::Draw() => {
+                games += 1;
+            }
+            RoundResult::Placements(places) => {
+                if let Some([p]) = places.first().map(|g| g.as_slice()) {
+                    *wins.entry(p).or_default() += 1;
+                }
+                games += 1;
+            }
+        }
+    }
+    (wins, games)
+}
+
 /*
 pub struct Round {
 }
@@ -175,6 +468,18 @@ impl Serialize for Round {
 }
 */
 
+impl RoundResult {
+    /// Checks if this result replaces an earlier one when it is recorded. Game wins replace the
+    /// same player's earlier total and a finishing order replaces the earlier order.
+    fn replaces(&self, earlier: &RoundResult) -> bool {
+        match (self, earlier) {
+            (RoundResult::Wins(p, _), RoundResult::Wins(q, _)) => p == q,
+            (RoundResult::Placements(_), RoundResult::Placements(_)) => true,
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for RoundStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -185,6 +490,7 @@ impl fmt::Display for RoundStatus {
                 Self::Uncertified => "Uncertified",
                 Self::Certified => "Certified",
                 Self::Dead => "Dead",
+                Self::Disputed => "Disputed",
             }
         )
     }
@@ -204,3 +510,200 @@ impl PartialEq for Round {
         self.id == other.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::player::Player;
+
+    /// A 50 minute round whose clock started at the given time
+    fn round_from(start: SystemTime) -> Round {
+        Round::with_id(Uuid::new_v4(), start, 0, 0, Duration::from_secs(3000))
+    }
+
+    fn mins(n: u64) -> Duration {
+        Duration::from_secs(60 * n)
+    }
+
+    #[test]
+    fn paused_time_doesnt_count_against_the_clock() {
+        let start = SystemTime::now() - mins(30);
+        let mut rnd = round_from(start);
+        rnd.pause(start + mins(10)).unwrap();
+        assert_eq!(rnd.time_left(), mins(40));
+        assert!(matches!(
+            rnd.pause(start + mins(11)),
+            Err(TournamentError::ClockPaused)
+        ));
+        rnd.resume(start + mins(15)).unwrap();
+        assert_eq!(rnd.time_paused, mins(5));
+        assert!(matches!(
+            rnd.resume(start + mins(16)),
+            Err(TournamentError::ClockRunning)
+        ));
+        // 20 minutes have passed, 5 of which were paused
+        rnd.pause(start + mins(20)).unwrap();
+        assert_eq!(rnd.time_left(), mins(35));
+        rnd.resume(start + mins(30)).unwrap();
+        assert_eq!(rnd.time_paused, mins(15));
+        assert!(rnd.time_left() <= mins(35));
+        assert!(rnd.time_left() > mins(34));
+    }
+
+    #[test]
+    fn paused_rounds_dont_expire() {
+        let start = SystemTime::now() - mins(60);
+        let mut rnd = round_from(start);
+        assert_eq!(rnd.time_left(), Duration::from_secs(0));
+        assert!(rnd.is_expired());
+        rnd.pause(start + mins(20)).unwrap();
+        assert_eq!(rnd.time_left(), mins(30));
+        assert!(!rnd.is_expired());
+        rnd.resume(start + mins(40)).unwrap();
+        // Only 40 of the 60 minutes have counted
+        assert!(!rnd.is_expired());
+        assert!(rnd.time_left() <= mins(10));
+        assert!(rnd.time_left() > mins(9));
+    }
+
+    #[test]
+    fn calling_time_starts_the_extra_turns() {
+        let start = SystemTime::now() - mins(60);
+        let mut rnd = round_from(start);
+        assert!(matches!(
+            rnd.take_extra_turn(),
+            Err(TournamentError::TimeNotCalled)
+        ));
+        // Calling time restarts a paused clock
+        rnd.pause(start + mins(45)).unwrap();
+        rnd.call_time(start + mins(50), 2).unwrap();
+        assert!(!rnd.is_paused());
+        assert_eq!(rnd.time_paused, mins(5));
+        assert!(!rnd.is_expired());
+        assert!(matches!(
+            rnd.call_time(start + mins(51), 2),
+            Err(TournamentError::TimeCalled)
+        ));
+        rnd.take_extra_turn().unwrap();
+        rnd.take_extra_turn().unwrap();
+        assert_eq!(
+            rnd.time_called,
+            Some(ExtraTurns {
+                allowed: 2,
+                taken: 2
+            })
+        );
+        assert!(matches!(
+            rnd.take_extra_turn(),
+            Err(TournamentError::NoExtraTurns)
+        ));
+    }
+
+    #[test]
+    fn finished_rounds_have_no_clock() {
+        let start = SystemTime::now();
+        let mut rnd = round_from(start);
+        rnd.kill_round();
+        assert!(matches!(
+            rnd.pause(start),
+            Err(TournamentError::NoActiveRound)
+        ));
+        assert!(matches!(
+            rnd.call_time(start, 5),
+            Err(TournamentError::NoActiveRound)
+        ));
+        assert!(!rnd.is_expired());
+    }
+
+    /// A best-of-three between two players
+    fn best_of_three() -> (Round, PlayerId, PlayerId) {
+        let mut rnd = round_from(SystemTime::now());
+        rnd.best_of = 3;
+        let (a, b) = (Player::new("A".into()).id, Player::new("B".into()).id);
+        rnd.add_player(a.clone());
+        rnd.add_player(b.clone());
+        (rnd, a, b)
+    }
+
+    #[test]
+    fn results_can_be_corrected() {
+        let (mut rnd, a, b) = best_of_three();
+        rnd.record_result(RoundResult::Wins(a.clone(), 2)).unwrap();
+        rnd.confirm_round(a.clone()).unwrap();
+        // A 2-2 match can't happen in a best-of-three
+        assert!(matches!(
+            rnd.record_result(RoundResult::Wins(b.clone(), 2)),
+            Err(TournamentError::InvalidResult)
+        ));
+        assert_eq!(rnd.results, vec![RoundResult::Wins(a.clone(), 2)]);
+        rnd.record_result(RoundResult::Wins(a.clone(), 0)).unwrap();
+        rnd.record_result(RoundResult::Wins(b.clone(), 2)).unwrap();
+        assert_eq!(
+            rnd.results,
+            vec![
+                RoundResult::Wins(a.clone(), 0),
+                RoundResult::Wins(b.clone(), 2)
+            ]
+        );
+        // Everyone needs to confirm the corrected result
+        assert!(rnd.confirmations.is_empty());
+        rnd.confirm_round(a).unwrap();
+        assert_eq!(
+            rnd.confirm_round(b.clone()).unwrap(),
+            RoundStatus::Certified
+        );
+        assert_eq!(rnd.winner, Some(b));
+    }
+
+    #[test]
+    fn results_can_be_sent_again() {
+        let (mut rnd, a, b) = best_of_three();
+        rnd.record_result(RoundResult::Wins(a.clone(), 2)).unwrap();
+        rnd.record_result(RoundResult::Wins(b.clone(), 1)).unwrap();
+        rnd.record_result(RoundResult::Wins(a.clone(), 2)).unwrap();
+        rnd.record_result(RoundResult::Wins(b.clone(), 1)).unwrap();
+        assert_eq!(
+            rnd.results,
+            vec![
+                RoundResult::Wins(a.clone(), 2),
+                RoundResult::Wins(b.clone(), 1)
+            ]
+        );
+        rnd.confirm_round(a.clone()).unwrap();
+        rnd.confirm_round(b).unwrap();
+        assert_eq!(rnd.winner, Some(a));
+    }
+
+    #[test]
+    fn finishing_orders_replace_each_other() {
+        let (mut rnd, a, b) = best_of_three();
+        rnd.record_result(RoundResult::Placements(vec![
+            vec![a.clone()],
+            vec![b.clone()],
+        ]))
+        .unwrap();
+        let order = RoundResult::Placements(vec![vec![b.clone()], vec![a]]);
+        rnd.record_result(order.clone()).unwrap();
+        rnd.record_result(order.clone()).unwrap();
+        assert_eq!(rnd.results, vec![order]);
+        assert_eq!(rnd.match_winner(), Some(b));
+    }
+
+    #[test]
+    fn each_draw_is_another_game() {
+        let (mut rnd, _, _) = best_of_three();
+        for _ in 0..3 {
+            rnd.record_result(RoundResult::Draw()).unwrap();
+        }
+        assert!(matches!(
+            rnd.record_result(RoundResult::Draw()),
+            Err(TournamentError::InvalidResult)
+        ));
+        assert_eq!(rnd.results.len(), 3);
+        assert_eq!(rnd.match_winner(), None);
+    }
+}
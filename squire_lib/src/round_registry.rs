@@ -13,6 +13,7 @@ use cycle_map::CycleMap;
 
 use crate::{
     error::TournamentError,
+    operations::OpContext,
     player::PlayerId,
     round::{Round, RoundId, RoundStatus},
 };
@@ -31,6 +32,9 @@ pub struct RoundRegistry {
     pub opponents: HashMap<PlayerId, HashSet<PlayerId>>,
     pub starting_table: u64,
     pub length: Duration,
+    /// The number of games in each new match. See [`Round::best_of`].
+    #[serde(default = "default_best_of")]
+    pub best_of: u8,
 }
 
 impl RoundRegistry {
@@ -41,6 +45,7 @@ impl RoundRegistry {
             opponents: HashMap::new(),
             starting_table,
             length: len,
+            best_of: default_best_of(),
         }
     }
 
@@ -83,10 +88,11 @@ impl RoundRegistry {
         Ok(())
     }
 
+    /// Counts the rounds that are still unresolved. Certified and dead rounds are finished.
     pub fn active_round_count(&self) -> usize {
         self.rounds
-            .iter()
-            .filter(|(_, r)| !r.is_certified())
+            .values()
+            .filter(|r| !r.is_certified() && r.status != RoundStatus::Dead)
             .count()
     }
 
@@ -102,11 +108,19 @@ impl RoundRegistry {
         }
     }
 
-    pub fn create_round(&mut self) -> RoundIdentifier {
+    pub fn create_round(&mut self, ctx: &mut OpContext) -> RoundIdentifier {
         let match_num = self.rounds.len() as u64;
         let table_number = self.get_table_number();
-        let round = Round::new(match_num, table_number, self.length);
+        let mut round = Round::with_id(
+            ctx.next_id(),
+            ctx.time,
+            match_num,
+            table_number,
+            self.length,
+        );
+        round.best_of = self.best_of;
         let digest = RoundIdentifier::Id(round.id.clone());
+        self.num_and_id.insert(round.id.clone(), match_num);
         self.rounds.insert(match_num, round);
         digest
     }
@@ -179,7 +193,9 @@ impl RoundRegistry {
         let mut nums: Vec<u64> = self
             .rounds
             .iter()
-            .filter(|(_, r)| r.players.contains(id) && r.is_certified())
+            .filter(|(_, r)| {
+                r.players.contains(id) && !r.is_certified() && r.status != RoundStatus::Dead
+            })
             .map(|(_, r)| r.match_number)
             .collect();
         nums.sort_unstable();
@@ -190,7 +206,44 @@ impl RoundRegistry {
         }
     }
 
+    /// Gets the seats that the player has sat in, ordered by match number. Byes and dead rounds
+    /// are not included.
+    pub fn get_seat_history(&self, id: &PlayerId) -> Vec<usize> {
+        let mut seats: Vec<(u64, usize)> = self
+            .rounds
+            .values()
+            .filter(|r| !r.is_bye && r.status != RoundStatus::Dead)
+            .filter_map(|r| Some((r.match_number, r.get_seat(id)?)))
+            .collect();
+        seats.sort_unstable();
+        seats.into_iter().map(|(_, s)| s).collect()
+    }
+
     pub fn set_round_length(&mut self, length: Duration) {
         self.length = length;
     }
 }
+
+fn default_best_of() -> u8 {
+    3
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn created_rounds_can_be_found_by_id_and_number() {
+        let mut reg = RoundRegistry::new(0, Duration::from_secs(3000));
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        let ident = reg.create_round(&mut ctx);
+        let id = reg.get_round_id(&ident).unwrap();
+        assert_eq!(reg.get_round(&ident).unwrap().id, id);
+        assert!(reg.get_mut_round(&ident).is_some());
+        assert_eq!(reg.get_round_id(&RoundIdentifier::Number(0)), Some(id));
+    }
+}
@@ -0,0 +1,167 @@
+pub use crate::{
+    error::TournamentError, player::PlayerId, player_registry::PlayerRegistry,
+    round_registry::RoundRegistry,
+};
+use crate::{
+    pairings::{balance_seats, Pairings},
+    player::PlayerStatus,
+    player_registry::PlayerIdentifier,
+    scoring::{Score, Standings},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A pairing system where every player plays every other player exactly once. The whole schedule
+/// is computed using the circle method when the first round is paired. If there is an odd number
+/// of players, a different player gets a bye each round.
+///
+/// Players that register after the schedule is made are not added to it. Should a player drop,
+/// their remaining opponents get byes instead.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RoundRobinPairings {
+    schedule: Vec<Pairings>,
+    next_round: usize,
+}
+
+impl RoundRobinPairings {
+    pub fn new() -> Self {
+        RoundRobinPairings {
+            schedule: Vec::new(),
+            next_round: 0,
+        }
+    }
+
+    /// Returns `true` if the schedule has been computed
+    pub fn is_scheduled(&self) -> bool {
+        !self.schedule.is_empty()
+    }
+
+    /// Gets the rounds in the schedule that have yet to be paired
+    pub fn upcoming_rounds(&self) -> &[Pairings] {
+        &self.schedule[self.next_round.min(self.schedule.len())..]
+    }
+
+    /// Gets the whole schedule, including rounds that have already been paired
+    pub fn schedule(&self) -> &[Pairings] {
+        &self.schedule
+    }
+
+    pub fn ready_to_pair(&self, rnd_reg: &RoundRegistry) -> bool {
+        rnd_reg.active_round_count() == 0
+            && (!self.is_scheduled() || self.next_round < self.schedule.len())
+    }
+
+    pub fn pair<S>(
+        &mut self,
+        players: &PlayerRegistry,
+        matches: &RoundRegistry,
+        mut standings: Standings<S>,
+    ) -> Option<Pairings>
+    where
+        S: Score,
+    {
+        if !self.ready_to_pair(matches) {
+            return None;
+        }
+        if !self.is_scheduled() {
+            let plyrs: Vec<PlayerId> = standings
+                .scores
+                .drain(0..)
+                .map(|(p, _)| p)
+                .filter(|p| Self::is_registered(players, p))
+                .rev()
+                .collect();
+            self.schedule = Self::circle_schedule(plyrs);
+            if !self.is_scheduled() {
+                return None;
+            }
+        }
+        let round = &self.schedule[self.next_round];
+        self.next_round += 1;
+        let mut digest = Pairings {
+            paired: Vec::with_capacity(round.paired.len()),
+            rejected: Vec::new(),
+        };
+        for pairing in &round.paired {
+            let (active, dropped): (Vec<PlayerId>, Vec<PlayerId>) = pairing
+                .iter()
+                .cloned()
+                .partition(|p| Self::is_registered(players, p));
+            if dropped.is_empty() {
+                digest.paired.push(balance_seats(active, matches));
+            } else {
+                digest.rejected.extend(active);
+            }
+        }
+        digest.rejected.extend(
+            round
+                .rejected
+                .iter()
+                .filter(|p| Self::is_registered(players, p))
+                .cloned(),
+        );
+        Some(digest)
+    }
+
+    fn is_registered(players: &PlayerRegistry, id: &PlayerId) -> bool {
+        players.get_player_status(&PlayerIdentifier::Id(id.clone()))
+            == Some(PlayerStatus::Registered)
+    }
+
+    /// Creates a schedule using the circle method. The first player stays fixed while everyone
+    /// else rotates around them. If there is an odd number of players, an empty seat is added to
+    /// the circle and whoever is paired against it gets a bye.
+    fn circle_schedule(plyrs: Vec<PlayerId>) -> Vec<Pairings> {
+        let mut circle: Vec<Option<PlayerId>> = plyrs.into_iter().map(Some).collect();
+        if circle.len() < 2 {
+            return Vec::new();
+        }
+        if circle.len() % 2 == 1 {
+            circle.push(None);
+        }
+        let count = circle.len();
+        let mut digest = Vec::with_capacity(count - 1);
+        for _ in 0..count - 1 {
+            let mut round = Pairings {
+                paired: Vec::with_capacity(count / 2),
+                rejected: Vec::new(),
+            };
+            for i in 0..count / 2 {
+                match (&circle[i], &circle[count - 1 - i]) {
+                    (Some(one), Some(two)) => round.paired.push(vec![one.clone(), two.clone()]),
+                    (Some(plyr), None) | (None, Some(plyr)) => round.rejected.push(plyr.clone()),
+                    (None, None) => {}
+                }
+            }
+            digest.push(round);
+            let last = circle.pop().unwrap();
+            circle.insert(1, last);
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{operations::OpContext, player::Player};
+
+    #[test]
+    fn dead_rounds_dont_block_pairing() {
+        let mut reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        let ident = reg.create_round(&mut ctx);
+        for name in ["Alice", "Bob"] {
+            reg.add_player_to_round(&ident, Player::new(name.into()).id)
+                .unwrap();
+        }
+        let sys = RoundRobinPairings::new();
+        assert!(!sys.ready_to_pair(&reg));
+        reg.kill_round(&ident).unwrap();
+        assert!(sys.ready_to_pair(&reg));
+    }
+}
@@ -1,14 +1,26 @@
 use serde::{Deserialize, Serialize};
 
-use crate::player::PlayerId;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    player::PlayerId,
+    round::{last_placements, Round, RoundId, RoundResult},
+    round_registry::RoundRegistry,
+};
 
 pub trait Score
 where
     Self: ToString,
 {
+    /// The value that players are primarily ranked by, e.g. match points
+    fn primary_score(&self) -> f64;
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Standings<S> {
     pub scores: Vec<(PlayerId, S)>,
 }
@@ -21,3 +33,158 @@ where
         Standings { scores }
     }
 }
+
+/// The parts of a round that scoring systems count. Scoring systems keep these instead of whole
+/// rounds so that a round can be taken back out of the standings after it changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CountedRound {
+    pub match_number: u64,
+    pub players: Vec<PlayerId>,
+    pub winner: Option<PlayerId>,
+    pub results: Vec<RoundResult>,
+    pub is_bye: bool,
+}
+
+/// The rounds that a scoring system has counted, which are kept up to date as rounds are
+/// certified, killed, or have their results changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct RoundCache {
+    rounds: HashMap<RoundId, CountedRound>,
+}
+
+impl CountedRound {
+    /// Gets the most recently recorded finishing order of the round, if any
+    pub fn placements(&self) -> Option<&Vec<Vec<PlayerId>>> {
+        last_placements(&self.results)
+    }
+}
+
+impl From<&Round> for CountedRound {
+    fn from(round: &Round) -> Self {
+        CountedRound {
+            match_number: round.match_number,
+            players: round.players.clone(),
+            winner: round.winner.clone(),
+            results: round.results.clone(),
+            is_bye: round.is_bye,
+        }
+    }
+}
+
+impl RoundCache {
+    /// Counts every certified round from `first_match` onwards
+    pub fn tally(round_reg: &RoundRegistry, first_match: u64) -> Self {
+        let rounds = round_reg
+            .rounds
+            .values()
+            .filter(|r| counts_in_phase(r, first_match))
+            .map(|r| (r.id.clone(), r.into()))
+            .collect();
+        RoundCache { rounds }
+    }
+
+    /// Counts the round, replacing it if it was already counted
+    pub fn insert(&mut self, round: &Round) -> &CountedRound {
+        self.rounds.insert(round.id.clone(), round.into());
+        &self.rounds[&round.id]
+    }
+
+    /// Takes a round back out of the cache, if it was counted
+    pub fn remove(&mut self, id: &RoundId) -> Option<CountedRound> {
+        self.rounds.remove(id)
+    }
+
+    /// Updates the cache after a round is certified, killed, or has its results changed. If the
+    /// cache hasn't been built yet, it is built from every round that counts.
+    pub fn update(
+        cache: &mut Option<Self>,
+        round_reg: &RoundRegistry,
+        round: &Round,
+        first_match: u64,
+    ) {
+        match cache {
+            Some(cache) => {
+                cache.remove(&round.id);
+                if counts_in_phase(round, first_match) {
+                    cache.insert(round);
+                }
+            }
+            None => *cache = Some(Self::tally(round_reg, first_match)),
+        }
+    }
+
+    /// Checks the cache against a full recount. This is always true if the cache hasn't been built
+    /// yet.
+    pub fn verify(cache: &Option<Self>, round_reg: &RoundRegistry, first_match: u64) -> bool {
+        cache
+            .as_ref()
+            .map(|c| c == &Self::tally(round_reg, first_match))
+            .unwrap_or(true)
+    }
+
+    /// Gets the cached rounds, or counts them if the cache hasn't been built yet
+    pub fn get_or_tally<'a>(
+        cache: &'a Option<Self>,
+        round_reg: &RoundRegistry,
+        first_match: u64,
+    ) -> Cow<'a, Self> {
+        match cache {
+            Some(cache) => Cow::Borrowed(cache),
+            None => Cow::Owned(Self::tally(round_reg, first_match)),
+        }
+    }
+
+    pub fn rounds(&self) -> impl Iterator<Item = &CountedRound> {
+        self.rounds.values()
+    }
+}
+
+/// Checks if a round counts towards the standings of the phase that starts at `first_match`
+pub(crate) fn counts_in_phase(round: &Round, first_match: u64) -> bool {
+    round.match_number >= first_match && round.is_certified()
+}
+
+/// Draws a pseudo-random number for the given item. The same seed and item always give the same
+/// number, which is used to break ties in standings in a way that can be reproduced.
+pub(crate) fn seeded_draw<T: Hash>(seed: u64, item: &T) -> u64 {
+    let mut hasher = FnvHasher(0xcbf2_9ce4_8422_2325 ^ seed);
+    item.hash(&mut hasher);
+    // Mix the bits so that similar items give very different numbers
+    let mut z = hasher.finish().wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A FNV-1a hasher. Unlike the standard library's hasher, its output is stable across platforms
+/// and versions of Rust.
+struct FnvHasher(u64);
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_only_depend_on_the_seed_and_item() {
+        assert_eq!(seeded_draw(7, &"Alice"), seeded_draw(7, &"Alice"));
+        assert_ne!(seeded_draw(7, &"Alice"), seeded_draw(7, &"Bob"));
+        assert_ne!(seeded_draw(7, &"Alice"), seeded_draw(8, &"Alice"));
+        // Similar items and seeds still give very different draws
+        let draws: Vec<u64> = (0..64).map(|s| seeded_draw(s, &"Alice")).collect();
+        assert!(draws.iter().any(|d| d >> 63 == 0));
+        assert!(draws.iter().any(|d| d >> 63 == 1));
+    }
+}
@@ -2,6 +2,8 @@ use core::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::{standard_scoring::StandardTiebreaker, tournament::ScoringPreset};
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum TournamentSetting {
     Format(String),
@@ -11,6 +13,10 @@ pub enum TournamentSetting {
     MaxDeckCount(u8),
     RequireCheckIn(bool),
     RequireDeckReg(bool),
+    TeamSize(u8),
+    ExtraTurns(u8),
+    BestOf(u8),
+    ScoringPreset(ScoringPreset),
     PairingSetting(PairingSetting),
     ScoringSetting(ScoringSetting),
 }
@@ -19,11 +25,16 @@ pub enum TournamentSetting {
 pub enum PairingSetting {
     Swiss(SwissPairingsSetting),
     Fluid(FluidPairingsSetting),
+    Elimination(EliminationPairingsSetting),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ScoringSetting {
     Standard(StandardScoringSetting),
+    Buchholz(BuchholzScoringSetting),
+    SonnebornBerger(SonnebornBergerScoringSetting),
+    Cumulative(CumulativeScoringSetting),
+    Placement(PlacementScoringSetting),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -31,12 +42,24 @@ pub enum ScoringSetting {
 pub enum SwissPairingsSetting {
     MatchSize(u8),
     DoCheckIns(bool),
+    OptimalPairings(bool),
+    RetryLimit(u8),
+    MinMatchSize(u8),
+    AllowRematches(bool),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[repr(C)]
 pub enum FluidPairingsSetting {
     MatchSize(u8),
+    MinMatchSize(u8),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum EliminationPairingsSetting {
+    DoubleElimination(bool),
+    GrandFinalReset(bool),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -56,6 +79,48 @@ pub enum StandardScoringSetting {
     IncludeGwp(bool),
     IncludeOppMwp(bool),
     IncludeOppGwp(bool),
+    ModernTiebreakers(bool),
+    TiebreakerOrder(Vec<StandardTiebreaker>),
+    TiebreakSeed(u64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum BuchholzScoringSetting {
+    WinPoints(f64),
+    DrawPoints(f64),
+    LossPoints(f64),
+    ByePoints(f64),
+    Median(bool),
+    TiebreakSeed(u64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum SonnebornBergerScoringSetting {
+    WinPoints(f64),
+    DrawPoints(f64),
+    LossPoints(f64),
+    ByePoints(f64),
+    TiebreakSeed(u64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum CumulativeScoringSetting {
+    WinPoints(f64),
+    DrawPoints(f64),
+    LossPoints(f64),
+    ByePoints(f64),
+    TiebreakSeed(u64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum PlacementScoringSetting {
+    PlacementPoints(Vec<f64>),
+    ByePoints(f64),
+    TiebreakSeed(u64),
 }
 
 impl fmt::Display for TournamentSetting {
@@ -83,6 +148,18 @@ impl fmt::Display for TournamentSetting {
             RequireDeckReg(s) => {
                 write!(f, "Deck Reg?: {}", if *s { "yes" } else { "no" })
             }
+            TeamSize(s) => {
+                write!(f, "Team Size: {s}")
+            }
+            ExtraTurns(s) => {
+                write!(f, "Extra Turns: {s}")
+            }
+            BestOf(s) => {
+                write!(f, "Best of: {s}")
+            }
+            ScoringPreset(s) => {
+                write!(f, "Scoring System: {s:?}")
+            }
             PairingSetting(s) => {
                 write!(f, "{s}")
             }
@@ -99,6 +176,7 @@ impl fmt::Display for PairingSetting {
         match self {
             Swiss(s) => write!(f, "{s}"),
             Fluid(s) => write!(f, "{s}"),
+            Elimination(s) => write!(f, "{s}"),
         }
     }
 }
@@ -108,6 +186,10 @@ impl fmt::Display for ScoringSetting {
         use ScoringSetting::*;
         match self {
             Standard(s) => write!(f, "{s}"),
+            Buchholz(s) => write!(f, "{s}"),
+            SonnebornBerger(s) => write!(f, "{s}"),
+            Cumulative(s) => write!(f, "{s}"),
+            Placement(s) => write!(f, "{s}"),
         }
     }
 }
@@ -118,6 +200,10 @@ impl fmt::Display for SwissPairingsSetting {
         match self {
             MatchSize(s) => write!(f, "Match Size: {s}"),
             DoCheckIns(s) => write!(f, "Check Ins?: {s}"),
+            OptimalPairings(s) => write!(f, "Optimal Pairings?: {s}"),
+            RetryLimit(s) => write!(f, "Retry Limit: {s}"),
+            MinMatchSize(s) => write!(f, "Min Match Size: {s}"),
+            AllowRematches(s) => write!(f, "Allow Rematches?: {s}"),
         }
     }
 }
@@ -127,6 +213,17 @@ impl fmt::Display for FluidPairingsSetting {
         use FluidPairingsSetting::*;
         match self {
             MatchSize(s) => write!(f, "Match Size: {s}"),
+            MinMatchSize(s) => write!(f, "Min Match Size: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for EliminationPairingsSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use EliminationPairingsSetting::*;
+        match self {
+            DoubleElimination(s) => write!(f, "Double Elimination?: {s}"),
+            GrandFinalReset(s) => write!(f, "Grand Final Reset?: {s}"),
         }
     }
 }
@@ -149,6 +246,76 @@ impl fmt::Display for StandardScoringSetting {
             IncludeGwp(s) => write!(f, "GWP?: {}", if *s { "yes" } else { "no" }),
             IncludeOppMwp(s) => write!(f, "Opp MWP?: {}", if *s { "yes" } else { "no" }),
             IncludeOppGwp(s) => write!(f, "Opp GWP?: {}", if *s { "yes" } else { "no" }),
+            ModernTiebreakers(s) => {
+                write!(f, "Modern Tiebreakers?: {}", if *s { "yes" } else { "no" })
+            }
+            TiebreakerOrder(s) => write!(
+                f,
+                "Tiebreakers: {}",
+                s.iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TiebreakSeed(s) => write!(f, "Tiebreak Seed: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for BuchholzScoringSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use BuchholzScoringSetting::*;
+        match self {
+            WinPoints(s) => write!(f, "Win: {s}"),
+            DrawPoints(s) => write!(f, "Draw: {s}"),
+            LossPoints(s) => write!(f, "Loss: {s}"),
+            ByePoints(s) => write!(f, "Bye: {s}"),
+            Median(s) => write!(f, "Median?: {}", if *s { "yes" } else { "no" }),
+            TiebreakSeed(s) => write!(f, "Tiebreak Seed: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for SonnebornBergerScoringSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SonnebornBergerScoringSetting::*;
+        match self {
+            WinPoints(s) => write!(f, "Win: {s}"),
+            DrawPoints(s) => write!(f, "Draw: {s}"),
+            LossPoints(s) => write!(f, "Loss: {s}"),
+            ByePoints(s) => write!(f, "Bye: {s}"),
+            TiebreakSeed(s) => write!(f, "Tiebreak Seed: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for CumulativeScoringSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CumulativeScoringSetting::*;
+        match self {
+            WinPoints(s) => write!(f, "Win: {s}"),
+            DrawPoints(s) => write!(f, "Draw: {s}"),
+            LossPoints(s) => write!(f, "Loss: {s}"),
+            ByePoints(s) => write!(f, "Bye: {s}"),
+            TiebreakSeed(s) => write!(f, "Tiebreak Seed: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for PlacementScoringSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use PlacementScoringSetting::*;
+        match self {
+            PlacementPoints(s) => write!(
+                f,
+                "Placement Points: {}",
+                s.iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ByePoints(s) => write!(f, "Bye: {s}"),
+            TiebreakSeed(s) => write!(f, "Tiebreak Seed: {s}"),
         }
     }
 }
@@ -3,22 +3,48 @@ pub use crate::{
     player::PlayerId,
     player_registry::PlayerIdentifier,
     player_registry::PlayerRegistry,
-    round::{Round, RoundResult},
+    round::{Round, RoundId, RoundResult},
     round_registry::RoundRegistry,
     scoring::{Score, Standings},
     settings::StandardScoringSetting,
 };
 
+use crate::scoring::{counts_in_phase, seeded_draw, CountedRound, RoundCache};
+
 use serde::{Deserialize, Serialize};
 
 use std::fmt::Write as _;
 use std::{
-    collections::{HashMap, HashSet},
-    fmt::Display,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display},
     string::ToString,
 };
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// The lowest match and game win percentage that a player can have when using modern tiebreakers
+const MIN_PERCENTAGE: f64 = 1.0 / 3.0;
+
+/// The values that players can be ranked by, e.g. opponent match win percentage
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum StandardTiebreaker {
+    MatchPoints,
+    GamePoints,
+    Mwp,
+    Gwp,
+    OppMwp,
+    OppGwp,
+}
+
+/// What ranks a player above the player ranked just below them in the standings
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum RankSeparator {
+    Tiebreaker(StandardTiebreaker),
+    RandomDraw,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[repr(C)]
 pub struct StandardScore {
     pub match_points: f64,
@@ -33,6 +59,13 @@ pub struct StandardScore {
     pub include_gwp: bool,
     pub include_opp_mwp: bool,
     pub include_opp_gwp: bool,
+    /// The player's place in the standings, starting from 1
+    #[serde(default)]
+    pub rank: usize,
+    /// What separated the player from the player ranked just below them. This is `None` for the
+    /// last player.
+    #[serde(default)]
+    pub separated_by: Option<RankSeparator>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -47,7 +80,18 @@ struct ScoreCounter {
     pub losses: u64,
     pub draws: u64,
     pub byes: u64,
-    pub opponents: HashSet<PlayerId>,
+    /// The number of times that the player has been in a match with each player. Each player is
+    /// their own opponent as well, which is filtered out when calculating percentages.
+    pub opponents: HashMap<PlayerId, u64>,
+}
+
+/// Running counters that are updated as rounds are certified and killed, so the standings don't
+/// need to tally every round each time that they are calculated
+#[derive(Debug, Clone, Default)]
+struct ScoreCache {
+    counters: HashMap<PlayerId, ScoreCounter>,
+    /// The rounds that have been counted, as they were when they were counted
+    rounds: RoundCache,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -67,6 +111,24 @@ pub struct StandardScoring {
     include_gwp: bool,
     include_opp_mwp: bool,
     include_opp_gwp: bool,
+    /// Calculates percentages as in the official Magic tournament rules. Every percentage has a
+    /// floor of 1/3 and byes are left out of opponents' percentages.
+    #[serde(default)]
+    modern_tiebreakers: bool,
+    /// The order in which players are ranked
+    #[serde(default = "default_tiebreakers")]
+    tiebreakers: Vec<StandardTiebreaker>,
+    /// The seed for the random draw that breaks ties that remain after every tiebreaker
+    #[serde(default)]
+    tiebreak_seed: u64,
+    /// This isn't serialized. Until it is rebuilt, the standings are tallied from scratch.
+    #[serde(skip)]
+    cache: Option<ScoreCache>,
+}
+
+fn default_tiebreakers() -> Vec<StandardTiebreaker> {
+    use StandardTiebreaker::*;
+    vec![MatchPoints, GamePoints, Mwp, Gwp, OppMwp, OppGwp]
 }
 
 impl StandardScoring {
@@ -100,6 +162,59 @@ impl StandardScoring {
             + self.game_loss_points * (counter.game_losses as f64)
     }
 
+    /// Calculates a player's match win percentage as seen by their opponents, i.e. without byes.
+    /// This is `None` if the player has only had byes.
+    fn calculate_opp_mwp(&self, counter: &ScoreCounter) -> Option<f64> {
+        let matches = counter.rounds - counter.byes;
+        (matches != 0).then(|| {
+            (self.calculate_match_points_without_byes(counter)
+                / (self.match_win_points * (matches as f64)))
+                .max(MIN_PERCENTAGE)
+        })
+    }
+
+    /// Calculates a player's game win percentage with the modern floor. This is `None` if the
+    /// player has yet to play a game.
+    fn calculate_floored_gwp(&self, counter: &ScoreCounter) -> Option<f64> {
+        (counter.games != 0).then(|| {
+            (self.calculate_game_points(counter) / (self.game_win_points * (counter.games as f64)))
+                .max(MIN_PERCENTAGE)
+        })
+    }
+
+    /// Gets the tiebreakers in the order that they are used. Tiebreakers that aren't included in
+    /// scores are skipped.
+    fn active_tiebreakers(&self) -> impl Iterator<Item = StandardTiebreaker> + '_ {
+        use StandardTiebreaker::*;
+        self.tiebreakers.iter().copied().filter(|t| match t {
+            MatchPoints => self.include_match_points,
+            GamePoints => self.include_game_points,
+            Mwp => self.include_mwp,
+            Gwp => self.include_gwp,
+            OppMwp => self.include_opp_mwp,
+            OppGwp => self.include_opp_gwp,
+        })
+    }
+
+    /// Finds the first tiebreaker that separates two scores, if any
+    fn separate(&self, this: &StandardScore, other: &StandardScore) -> Option<StandardTiebreaker> {
+        self.active_tiebreakers()
+            .find(|t| this.get(*t).total_cmp(&other.get(*t)).is_ne())
+    }
+
+    /// Compares two players. Ties that remain after every tiebreaker are settled by a random draw.
+    fn compare(
+        &self,
+        (this_id, this): &(PlayerId, StandardScore),
+        (other_id, other): &(PlayerId, StandardScore),
+    ) -> Ordering {
+        match self.separate(this, other) {
+            Some(t) => this.get(t).total_cmp(&other.get(t)),
+            None => seeded_draw(self.tiebreak_seed, this_id)
+                .cmp(&seeded_draw(self.tiebreak_seed, other_id)),
+        }
+    }
+
     pub fn new() -> Self {
         StandardScoring {
             match_win_points: 3.0,
@@ -116,6 +231,10 @@ impl StandardScoring {
             include_gwp: true,
             include_opp_mwp: true,
             include_opp_gwp: true,
+            modern_tiebreakers: false,
+            tiebreakers: default_tiebreakers(),
+            tiebreak_seed: 0,
+            cache: None,
         }
     }
 
@@ -145,6 +264,8 @@ impl StandardScoring {
             }
             IncludeByes(b) => {
                 self.include_byes = b;
+                // This changes which rounds are counted
+                self.cache = None;
             }
             IncludeMatchPoints(b) => {
                 self.include_match_points = b;
@@ -164,32 +285,90 @@ impl StandardScoring {
             IncludeOppGwp(b) => {
                 self.include_opp_gwp = b;
             }
+            ModernTiebreakers(b) => {
+                self.modern_tiebreakers = b;
+            }
+            TiebreakerOrder(order) => {
+                self.tiebreakers = order;
+            }
+            TiebreakSeed(seed) => {
+                self.tiebreak_seed = seed;
+            }
         }
     }
 
+    /// Checks if a round counts towards the standings. Rounds before `first_match` belong to an
+    /// earlier phase of the tournament.
+    fn counts(&self, round: &Round, first_match: u64) -> bool {
+        counts_in_phase(round, first_match) && (self.include_byes || !round.is_bye)
+    }
+
+    /// Tallies every round that counts from scratch
+    fn tally(&self, round_reg: &RoundRegistry, first_match: u64) -> ScoreCache {
+        let mut cache = ScoreCache::default();
+        for round in round_reg
+            .rounds
+            .values()
+            .filter(|r| self.counts(r, first_match))
+        {
+            cache.add_round(round);
+        }
+        cache
+    }
+
+    /// Updates the running counters after a round is certified, killed, or has its results
+    /// changed. If the counters haven't been built yet, they are built from every round.
+    pub fn update_round(&mut self, round_reg: &RoundRegistry, round: &Round, first_match: u64) {
+        let cache = match self.cache.take() {
+            Some(mut cache) => {
+                cache.remove_round(&round.id);
+                if self.counts(round, first_match) {
+                    cache.add_round(round);
+                }
+                cache
+            }
+            None => self.tally(round_reg, first_match),
+        };
+        self.cache = Some(cache);
+    }
+
+    /// Checks the running counters against a full recount of every round. This is always true if
+    /// the counters haven't been built yet.
+    pub fn verify_cache(&self, round_reg: &RoundRegistry, first_match: u64) -> bool {
+        self.cache
+            .as_ref()
+            .map(|c| c.counters == self.tally(round_reg, first_match).counters)
+            .unwrap_or(true)
+    }
+
+    /// Calculates the standings from the rounds from `first_match` onwards
     pub fn get_standings(
         &self,
         player_reg: &PlayerRegistry,
         round_reg: &RoundRegistry,
+        first_match: u64,
     ) -> Standings<StandardScore> {
-        let mut counters: HashMap<PlayerId, ScoreCounter> = player_reg
+        let tally;
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => {
+                tally = self.tally(round_reg, first_match);
+                &tally
+            }
+        };
+        // Dropped players are tallied too as they still count towards their opponents' scores
+        let counters: HashMap<PlayerId, ScoreCounter> = player_reg
             .players
-            .iter()
-            .filter(|(_, p)| p.can_play())
-            .map(|(id, _)| (id.clone(), ScoreCounter::new(id.clone())))
+            .keys()
+            .map(|id| {
+                let counter = cache
+                    .counters
+                    .get(id)
+                    .cloned()
+                    .unwrap_or_else(|| ScoreCounter::new(id.clone()));
+                (id.clone(), counter)
+            })
             .collect();
-        for (id, round) in round_reg.rounds.iter() {
-            if !round.is_certified() {
-                continue;
-            }
-            if round.is_bye && !self.include_byes {
-                continue;
-            }
-            for p in round.players.iter() {
-                let counter = counters.get_mut(p).unwrap();
-                counter.add_round(round)
-            }
-        }
         // We have tallied everyone's round results. Time to calculate everyone's scores
         let mut digest: HashMap<PlayerId, StandardScore> = HashMap::with_capacity(counters.len());
         for (id, counter) in &counters {
@@ -200,11 +379,59 @@ impl StandardScoring {
             // This also filters out folks that haven't played a match yet
             if counter.rounds != counter.byes {
                 score.mwp = score.match_points / (self.match_win_points * (counter.rounds as f64));
+            }
+            if counter.games != 0 {
                 score.gwp = score.game_points / (self.game_win_points * (counter.games as f64));
             }
+            if self.modern_tiebreakers {
+                if counter.rounds != 0 {
+                    score.mwp = (score.match_points
+                        / (self.match_win_points * (counter.rounds as f64)))
+                        .max(MIN_PERCENTAGE);
+                }
+                score.gwp = self.calculate_floored_gwp(counter).unwrap_or_default();
+            }
             digest.insert(id.clone(), score);
         }
-        for (id, counter) in &counters {
+        if self.modern_tiebreakers {
+            self.calculate_modern_opp_percentages(&counters, &mut digest);
+        } else {
+            self.calculate_opp_percentages(&counters, &mut digest);
+        }
+        digest.retain(|id, _| {
+            player_reg
+                .players
+                .get(id)
+                .map(|p| p.can_play())
+                .unwrap_or_default()
+        });
+        let mut results: Vec<(PlayerId, StandardScore)> = digest.drain().collect();
+        results.sort_by(|a, b| self.compare(a, b));
+        // Standings go from last place to first
+        let count = results.len();
+        for i in 0..count {
+            let separated_by = match i {
+                0 => None,
+                _ => Some(
+                    self.separate(&results[i].1, &results[i - 1].1)
+                        .map(RankSeparator::Tiebreaker)
+                        .unwrap_or(RankSeparator::RandomDraw),
+                ),
+            };
+            let score = &mut results[i].1;
+            score.rank = count - i;
+            score.separated_by = separated_by;
+        }
+        Standings::new(results)
+    }
+
+    /// Calculates opponent percentages by pooling all of the opponents' matches and games
+    fn calculate_opp_percentages(
+        &self,
+        counters: &HashMap<PlayerId, ScoreCounter>,
+        digest: &mut HashMap<PlayerId, StandardScore>,
+    ) {
+        for (id, counter) in counters {
             // If your only round was a bye, your percentages stay at 0
             // This also filters out folks that haven't played a match yet
             if counter.rounds == counter.byes {
@@ -214,18 +441,62 @@ impl StandardScoring {
             let mut opp_matches: u64 = 0;
             let mut opp_gp: f64 = 0.0;
             let mut opp_games: u64 = 0;
-            for plyr in counter.opponents.iter().filter(|i| *i != id) {
+            // Sorted so that the sums below come out the same between replays
+            let mut opps: Vec<&PlayerId> = counter
+                .opponents
+                .keys()
+                .filter(|i| *i != id && counters.contains_key(*i))
+                .collect();
+            opps.sort();
+            for plyr in opps {
                 opp_mp += self.calculate_match_points_without_byes(&counters[plyr]);
                 opp_matches += counters[plyr].rounds - counters[plyr].byes;
                 opp_gp += self.calculate_game_points(&counters[plyr]);
                 opp_games += counters[plyr].games;
             }
-            digest.get_mut(id).unwrap().opp_mwp = opp_mp / (opp_matches as f64);
-            digest.get_mut(id).unwrap().opp_gwp = opp_gp / (opp_games as f64);
+            if opp_matches != 0 {
+                digest.get_mut(id).unwrap().opp_mwp =
+                    opp_mp / (self.match_win_points * (opp_matches as f64));
+            }
+            if opp_games != 0 {
+                digest.get_mut(id).unwrap().opp_gwp =
+                    opp_gp / (self.game_win_points * (opp_games as f64));
+            }
+        }
+    }
+
+    /// Calculates opponent percentages as the average of each opponent's percentages, each of
+    /// which has a floor of 1/3. Opponents that have only had byes are left out.
+    fn calculate_modern_opp_percentages(
+        &self,
+        counters: &HashMap<PlayerId, ScoreCounter>,
+        digest: &mut HashMap<PlayerId, StandardScore>,
+    ) {
+        for (id, counter) in counters {
+            // Sorted so that the sums below come out the same between replays
+            let mut opp_ids: Vec<&PlayerId> =
+                counter.opponents.keys().filter(|i| *i != id).collect();
+            opp_ids.sort();
+            let opps: Vec<&ScoreCounter> = opp_ids
+                .into_iter()
+                .filter_map(|i| counters.get(i))
+                .collect();
+            let mwps: Vec<f64> = opps
+                .iter()
+                .filter_map(|c| self.calculate_opp_mwp(c))
+                .collect();
+            let gwps: Vec<f64> = opps
+                .iter()
+                .filter_map(|c| self.calculate_floored_gwp(c))
+                .collect();
+            let score = digest.get_mut(id).unwrap();
+            if !mwps.is_empty() {
+                score.opp_mwp = mwps.iter().sum::<f64>() / (mwps.len() as f64);
+            }
+            if !gwps.is_empty() {
+                score.opp_gwp = gwps.iter().sum::<f64>() / (gwps.len() as f64);
+            }
         }
-        let mut results: Vec<(PlayerId, StandardScore)> = digest.drain().collect();
-        results.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
-        Standings::new(results)
     }
 }
 
@@ -251,11 +522,32 @@ impl StandardScore {
             include_gwp,
             include_opp_mwp,
             include_opp_gwp,
+            rank: 0,
+            separated_by: None,
+        }
+    }
+}
+
+impl StandardScore {
+    /// Gets the value of the given tiebreaker
+    pub fn get(&self, tiebreaker: StandardTiebreaker) -> f64 {
+        use StandardTiebreaker::*;
+        match tiebreaker {
+            MatchPoints => self.match_points,
+            GamePoints => self.game_points,
+            Mwp => self.mwp,
+            Gwp => self.gwp,
+            OppMwp => self.opp_mwp,
+            OppGwp => self.opp_gwp,
         }
     }
 }
 
-impl Score for StandardScore {}
+impl Score for StandardScore {
+    fn primary_score(&self) -> f64 {
+        self.match_points
+    }
+}
 
 impl Display for StandardScore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -305,63 +597,117 @@ impl ScoreCounter {
             losses: 0,
             draws: 0,
             byes: 0,
-            opponents: HashSet::new(),
+            opponents: HashMap::new(),
         }
     }
 
-    fn add_round(&mut self, round: &Round) {
-        match &round.winner {
-            Some(winner) => {
-                if winner == &self.player {
-                    self.add_win(&round.players);
-                } else {
-                    self.add_loss(&round.players);
-                }
+    fn add_round(&mut self, round: &CountedRound) {
+        self.count_round(round, true);
+    }
+
+    /// Takes a round that was added back out
+    fn remove_round(&mut self, round: &CountedRound) {
+        self.count_round(round, false);
+    }
+
+    fn count_round(&mut self, round: &CountedRound, adding: bool) {
+        let tally = |n: &mut u64, by: u64| {
+            if adding {
+                *n += by;
+            } else {
+                *n = n.saturating_sub(by);
             }
-            None => {
-                if round.is_bye {
-                    self.add_bye();
-                } else {
-                    self.add_draw(&round.players);
-                }
+        };
+        tally(&mut self.rounds, 1);
+        // Byes record their player as the winner, but they aren't match wins
+        if round.is_bye {
+            tally(&mut self.byes, 1);
+            return;
+        }
+        match &round.winner {
+            Some(winner) if winner == &self.player => tally(&mut self.wins, 1),
+            Some(_) => tally(&mut self.losses, 1),
+            None => tally(&mut self.draws, 1),
+        }
+        for plyr in &round.players {
+            let count = self.opponents.entry(plyr.clone()).or_default();
+            tally(count, 1);
+            if *count == 0 {
+                self.opponents.remove(plyr);
             }
         }
+        // Each result is shared by everyone in the match, so this works for any size of pod. A
+        // game won by one player is a loss for each of the others.
         for result in &round.results {
             match result {
                 RoundResult::Draw() => {
-                    self.game_draws += 1;
+                    tally(&mut self.games, 1);
+                    tally(&mut self.game_draws, 1);
                 }
                 RoundResult::Wins(p_id, count) => {
+                    let count = *count as u64;
+                    tally(&mut self.games, count);
                     if p_id == &self.player {
-                        self.game_wins += 1;
+                        tally(&mut self.game_wins, count);
                     } else {
-                        self.game_losses += 1;
+                        tally(&mut self.game_losses, count);
+                    }
+                }
+                // A finishing order is a single game. It is a draw if first place is shared.
+                RoundResult::Placements(places) => {
+                    tally(&mut self.games, 1);
+                    match places.first() {
+                        Some(first) if !first.contains(&self.player) => {
+                            tally(&mut self.game_losses, 1)
+                        }
+                        Some(first) if first.len() == 1 => tally(&mut self.game_wins, 1),
+                        _ => tally(&mut self.game_draws, 1),
                     }
                 }
             }
         }
     }
+}
 
-    fn add_win(&mut self, players: &HashSet<PlayerId>) {
-        self.wins += 1;
-        self.games += 1;
-        self.opponents.extend(players.clone());
-    }
-
-    fn add_loss(&mut self, players: &HashSet<PlayerId>) {
-        self.losses += 1;
-        self.games += 1;
-        self.opponents.extend(players.clone());
+impl ScoreCache {
+    fn add_round(&mut self, round: &Round) {
+        let round = self.rounds.insert(round);
+        for plyr in &round.players {
+            self.counters
+                .entry(plyr.clone())
+                .or_insert_with(|| ScoreCounter::new(plyr.clone()))
+                .add_round(round);
+        }
     }
 
-    fn add_draw(&mut self, players: &HashSet<PlayerId>) {
-        self.draws += 1;
-        self.games += 1;
-        self.opponents.extend(players.clone());
+    /// Takes a round back out of the counters, if it was counted
+    fn remove_round(&mut self, id: &RoundId) {
+        let Some(round) = self.rounds.remove(id) else {
+            return;
+        };
+        for plyr in &round.players {
+            if let Some(counter) = self.counters.get_mut(plyr) {
+                counter.remove_round(&round);
+                if counter.rounds == 0 {
+                    self.counters.remove(plyr);
+                }
+            }
+        }
     }
+}
 
-    fn add_bye(&mut self) {
-        self.byes += 1;
+impl Display for StandardTiebreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use StandardTiebreaker::*;
+        let s = match self {
+            MatchPoints => "MP",
+            GamePoints => "GP",
+            Mwp => "MW",
+            Gwp => "GW",
+            OppMwp => "OMW",
+            OppGwp => "OGW",
+        };
+        write!(f, "{s}")
     }
 }
 
@@ -370,3 +716,195 @@ impl Default for StandardScoring {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{operations::OpContext, player::Player};
+
+    /// Three players that each get a bye and play each other once. The first player beats the
+    /// second 2-0 and the third 2-1, and the third beats the second 2-0.
+    fn fixture() -> (PlayerRegistry, RoundRegistry, Vec<PlayerId>) {
+        let mut plyr_reg = PlayerRegistry::new();
+        let plyrs: Vec<PlayerId> = ["A", "B", "C"]
+            .into_iter()
+            .map(|name| {
+                let plyr = Player::new(name.into());
+                let id = plyr.id.clone();
+                plyr_reg.import_player(plyr).unwrap();
+                id
+            })
+            .collect();
+        let mut rnd_reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        let mut add_round = |players: &[usize], results: Vec<RoundResult>| {
+            let ident = rnd_reg.create_round(&mut ctx);
+            for p in players {
+                rnd_reg
+                    .add_player_to_round(&ident, plyrs[*p].clone())
+                    .unwrap();
+            }
+            let rnd = rnd_reg.get_mut_round(&ident).unwrap();
+            if results.is_empty() {
+                rnd.record_bye().unwrap();
+            } else {
+                rnd.force_certify(results).unwrap();
+            }
+        };
+        add_round(&[0, 1], vec![RoundResult::Wins(plyrs[0].clone(), 2)]);
+        add_round(&[2], Vec::new());
+        add_round(
+            &[0, 2],
+            vec![
+                RoundResult::Wins(plyrs[0].clone(), 2),
+                RoundResult::Wins(plyrs[2].clone(), 1),
+            ],
+        );
+        add_round(&[1], Vec::new());
+        add_round(&[2, 1], vec![RoundResult::Wins(plyrs[2].clone(), 2)]);
+        add_round(&[0], Vec::new());
+        (plyr_reg, rnd_reg, plyrs)
+    }
+
+    fn scores(sys: &StandardScoring) -> Vec<StandardScore> {
+        let (plyr_reg, rnd_reg, plyrs) = fixture();
+        let standings = sys.get_standings(&plyr_reg, &rnd_reg, 0);
+        plyrs
+            .iter()
+            .map(|p| {
+                standings
+                    .scores
+                    .iter()
+                    .find(|(id, _)| id == p)
+                    .unwrap()
+                    .1
+                    .clone()
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn modern_percentages() {
+        let mut sys = StandardScoring::new();
+        sys.update_setting(StandardScoringSetting::ModernTiebreakers(true));
+        let [a, b, c]: [StandardScore; 3] = scores(&sys).try_into().unwrap();
+        // Byes count towards a player's own match win percentage
+        assert_close(a.match_points, 9.0);
+        assert_close(a.mwp, 1.0);
+        assert_close(b.mwp, 1.0 / 3.0);
+        assert_close(c.mwp, 6.0 / 9.0);
+        // Game win percentages have a floor of 1/3
+        assert_close(a.gwp, 12.0 / 15.0);
+        assert_close(b.gwp, 1.0 / 3.0);
+        assert_close(c.gwp, 9.0 / 15.0);
+        // Byes are left out of opponents' percentages, i.e. B is at the floor, not 1/3 from
+        // their bye, and C is at 1/2, not 2/3
+        assert_close(a.opp_mwp, (1.0 / 3.0 + 1.0 / 2.0) / 2.0);
+        assert_close(b.opp_mwp, (1.0 + 1.0 / 2.0) / 2.0);
+        assert_close(c.opp_mwp, (1.0 + 1.0 / 3.0) / 2.0);
+        assert_close(a.opp_gwp, (1.0 / 3.0 + 9.0 / 15.0) / 2.0);
+        assert_close(b.opp_gwp, (12.0 / 15.0 + 9.0 / 15.0) / 2.0);
+        assert_close(c.opp_gwp, (12.0 / 15.0 + 1.0 / 3.0) / 2.0);
+    }
+
+    #[test]
+    fn pooled_percentages() {
+        let sys = StandardScoring::new();
+        let [a, b, c]: [StandardScore; 3] = scores(&sys).try_into().unwrap();
+        assert_close(a.mwp, 1.0);
+        assert_close(b.mwp, 3.0 / 9.0);
+        assert_close(c.mwp, 6.0 / 9.0);
+        // There is no floor
+        assert_close(b.gwp, 0.0);
+        // Opponents' matches and games are pooled, without their byes
+        assert_close(a.opp_mwp, 3.0 / 12.0);
+        assert_close(b.opp_mwp, 9.0 / 12.0);
+        assert_close(c.opp_mwp, 6.0 / 12.0);
+        assert_close(a.opp_gwp, 9.0 / 27.0);
+        assert_close(b.opp_gwp, 21.0 / 30.0);
+        assert_close(c.opp_gwp, 12.0 / 27.0);
+    }
+
+    #[test]
+    fn tiebreakers_are_used_in_the_configured_order() {
+        use StandardTiebreaker::*;
+        let (plyr_reg, rnd_reg, plyrs) = fixture();
+        let ranks = |sys: &StandardScoring| -> Vec<PlayerId> {
+            let standings = sys.get_standings(&plyr_reg, &rnd_reg, 0);
+            standings.scores.into_iter().rev().map(|(p, _)| p).collect()
+        };
+        let mut sys = StandardScoring::new();
+        sys.update_setting(StandardScoringSetting::ModernTiebreakers(true));
+        assert_eq!(
+            ranks(&sys),
+            vec![plyrs[0].clone(), plyrs[2].clone(), plyrs[1].clone()]
+        );
+        // Opponent match win percentage reverses the order
+        sys.update_setting(StandardScoringSetting::TiebreakerOrder(vec![
+            OppMwp,
+            MatchPoints,
+        ]));
+        assert_eq!(
+            ranks(&sys),
+            vec![plyrs[1].clone(), plyrs[2].clone(), plyrs[0].clone()]
+        );
+        let standings = sys.get_standings(&plyr_reg, &rnd_reg, 0);
+        assert!(standings
+            .scores
+            .iter()
+            .skip(1)
+            .all(|(_, s)| s.separated_by == Some(RankSeparator::Tiebreaker(OppMwp))));
+        // Tiebreakers that aren't included are skipped
+        sys.update_setting(StandardScoringSetting::IncludeOppMwp(false));
+        assert_eq!(
+            ranks(&sys),
+            vec![plyrs[0].clone(), plyrs[2].clone(), plyrs[1].clone()]
+        );
+    }
+
+    #[test]
+    fn ties_are_broken_by_a_seeded_draw() {
+        let plyrs: Vec<Player> = (0..8).map(|i| Player::new(format!("Player {i}"))).collect();
+        // The registries hold the same players, but they are added in a different order
+        let mut forwards = PlayerRegistry::new();
+        let mut backwards = PlayerRegistry::new();
+        for plyr in &plyrs {
+            forwards.import_player(plyr.clone()).unwrap();
+        }
+        for plyr in plyrs.iter().rev() {
+            backwards.import_player(plyr.clone()).unwrap();
+        }
+        let rnd_reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let order = |sys: &StandardScoring, plyr_reg: &PlayerRegistry| -> Vec<PlayerId> {
+            let standings = sys.get_standings(plyr_reg, &rnd_reg, 0);
+            assert!(standings
+                .scores
+                .iter()
+                .skip(1)
+                .all(|(_, s)| s.separated_by == Some(RankSeparator::RandomDraw)));
+            standings.scores.into_iter().map(|(p, _)| p).collect()
+        };
+        let mut sys = StandardScoring::new();
+        let mut leaders = Vec::new();
+        for seed in 0..16 {
+            sys.update_setting(StandardScoringSetting::TiebreakSeed(seed));
+            let digest = order(&sys, &forwards);
+            assert_eq!(digest, order(&sys, &forwards));
+            assert_eq!(digest, order(&sys, &backwards));
+            leaders.push(digest.last().cloned().unwrap());
+        }
+        // Changing the seed changes the draw
+        assert!(leaders.iter().any(|p| p != &leaders[0]));
+    }
+}
@@ -3,7 +3,8 @@ pub use crate::{
     round_registry::RoundRegistry, settings::SwissPairingsSetting,
 };
 use crate::{
-    pairings::Pairings,
+    matching::{max_weight_matching, Edge},
+    pairings::{balance_seats, pod_sizes, Pairings},
     player::PlayerStatus,
     player_registry::PlayerIdentifier,
     scoring::{Score, Standings},
@@ -19,6 +20,20 @@ pub struct SwissPairings {
     players_per_match: u8,
     do_check_ins: bool,
     check_ins: HashSet<PlayerId>,
+    #[serde(default)]
+    optimal_pairings: bool,
+    #[serde(default = "default_retry_limit")]
+    retry_limit: u8,
+    #[serde(default)]
+    min_players_per_match: Option<u8>,
+    /// If set, a player that can only be paired through a rematch is paired with the next
+    /// players in the standings instead of being left out
+    #[serde(default)]
+    allow_rematches: bool,
+}
+
+fn default_retry_limit() -> u8 {
+    5
 }
 
 impl SwissPairings {
@@ -27,6 +42,10 @@ impl SwissPairings {
             players_per_match,
             do_check_ins: false,
             check_ins: HashSet::new(),
+            optimal_pairings: false,
+            retry_limit: default_retry_limit(),
+            min_players_per_match: None,
+            allow_rematches: false,
         }
     }
 
@@ -47,6 +66,18 @@ impl SwissPairings {
             DoCheckIns(b) => {
                 self.do_check_ins = b;
             }
+            OptimalPairings(b) => {
+                self.optimal_pairings = b;
+            }
+            RetryLimit(n) => {
+                self.retry_limit = n;
+            }
+            MinMatchSize(s) => {
+                self.min_players_per_match = Some(s);
+            }
+            AllowRematches(b) => {
+                self.allow_rematches = b;
+            }
         }
     }
 
@@ -61,12 +92,50 @@ impl SwissPairings {
 
     fn valid_pairing(&self, matches: &RoundRegistry, known: &[&PlayerId], new: &PlayerId) -> bool {
         if let Some(opps) = matches.opponents.get(new) {
-            known.iter().any(|p| !opps.contains(p))
+            known.iter().all(|p| !opps.contains(p))
         } else {
             true
         }
     }
 
+    /// Walks the list of players (which is sorted from highest ranked to lowest) to find a
+    /// pairing of the given size for the first player. Returns the indices of the players in the
+    /// pairing in ascending order.
+    ///
+    /// Should the walk run off the end of the list, the last player added to the pairing is
+    /// removed and the walk resumes from just after their position. The walk backs up at most
+    /// `retry_limit` times in total, after which the first player is treated as unpairable and
+    /// `None` is returned.
+    fn find_pairing(
+        &self,
+        matches: &RoundRegistry,
+        plyrs: &[PlayerId],
+        size: usize,
+    ) -> Option<Vec<usize>> {
+        let mut indices: Vec<usize> = Vec::with_capacity(size);
+        indices.push(0);
+        let mut next = 1;
+        let mut retries = 0;
+        loop {
+            while indices.len() < size && next < plyrs.len() {
+                let known: Vec<&PlayerId> = indices.iter().map(|i| &plyrs[*i]).collect();
+                if self.valid_pairing(matches, &known, &plyrs[next]) {
+                    indices.push(next);
+                }
+                next += 1;
+            }
+            if indices.len() == size {
+                return Some(indices);
+            }
+            // The first player is fixed, so there is nothing left to try
+            if indices.len() == 1 || retries >= self.retry_limit {
+                return None;
+            }
+            retries += 1;
+            next = indices.pop().unwrap() + 1;
+        }
+    }
+
     pub fn pair<S>(
         &mut self,
         players: &PlayerRegistry,
@@ -79,49 +148,363 @@ impl SwissPairings {
         if !self.ready_to_pair(players, matches) {
             return None;
         }
-        let mut plyrs: Vec<PlayerId> = standings
+        let plyrs: Vec<(PlayerId, f64)> = standings
             .scores
             .drain(0..)
-            .filter_map(|(p, _)| {
+            .filter_map(|(p, s)| {
                 if players.get_player_status(&PlayerIdentifier::Id(p.clone()))?
                     == PlayerStatus::Registered
                 {
-                    Some(p)
+                    Some((p, s.primary_score()))
                 } else {
                     None
                 }
             })
             .rev()
             .collect();
+        if self.optimal_pairings && self.players_per_match == 2 {
+            return Some(self.optimal_pair(matches, plyrs));
+        }
+        let mut plyrs: Vec<PlayerId> = plyrs.into_iter().map(|(p, _)| p).collect();
         let mut digest = Pairings {
             paired: Vec::with_capacity(plyrs.len() / self.players_per_match as usize + 1),
             rejected: Vec::new(),
         };
-        while plyrs.len() > self.players_per_match as usize {
-            let mut index_buffer: Vec<usize> = Vec::with_capacity(self.players_per_match as usize);
-            let mut id_buffer: Vec<&PlayerId> = Vec::with_capacity(self.players_per_match as usize);
-            index_buffer.push(0);
-            id_buffer.push(&plyrs[0]);
-            for (i, _) in plyrs.iter().enumerate().skip(1) {
-                if self.valid_pairing(matches, &id_buffer, &plyrs[i]) {
-                    index_buffer.push(i);
-                    id_buffer.push(&plyrs[i]);
-                    if index_buffer.len() == self.players_per_match as usize {
-                        break;
+        let min = self.min_players_per_match.unwrap_or(self.players_per_match);
+        while let Some(size) = pod_sizes(plyrs.len(), min, self.players_per_match)
+            .first()
+            .copied()
+        {
+            match self.find_pairing(matches, &plyrs, size) {
+                Some(indices) => {
+                    let pairing: Vec<PlayerId> =
+                        indices.iter().map(|i| plyrs[*i].clone()).collect();
+                    for i in indices.into_iter().rev() {
+                        plyrs.remove(i);
                     }
+                    digest.paired.push(balance_seats(pairing, matches));
                 }
-            }
-            if index_buffer.len() == self.players_per_match as usize {
-                let mut pairing: Vec<PlayerId> =
-                    Vec::with_capacity(self.players_per_match as usize);
-                for i in index_buffer {
-                    pairing.push(plyrs[i].clone());
+                // The highest ranked player can't be paired without a rematch, so they are either
+                // paired with the next players in the standings as a last resort or left out
+                None if self.allow_rematches => {
+                    let pairing: Vec<PlayerId> = plyrs.drain(0..size).collect();
+                    digest.paired.push(balance_seats(pairing, matches));
+                }
+                None => {
+                    digest.rejected.push(plyrs.remove(0));
                 }
-                digest.paired.push(pairing);
-            } else {
-                digest.rejected.push(plyrs.pop().unwrap());
             }
         }
+        digest.rejected.extend(plyrs.drain(0..).rev());
         Some(digest)
     }
+
+    /// Pairs players in two-player matches using a maximum weight matching. The pairings
+    /// minimize the total (squared) score difference between opponents and never contain a
+    /// rematch unless there is no way to pair everyone without one, in which case as few
+    /// rematches as possible are used. If there is an odd number of players, the lowest ranked
+    /// player that has not had a bye is rejected (and so receives the bye).
+    ///
+    /// The players are expected to be ordered from highest ranked to lowest.
+    fn optimal_pair(&self, matches: &RoundRegistry, plyrs: Vec<(PlayerId, f64)>) -> Pairings {
+        let count = plyrs.len();
+        let mut digest = Pairings {
+            paired: Vec::with_capacity(count / 2),
+            rejected: Vec::new(),
+        };
+        if count < 2 {
+            digest.rejected.extend(plyrs.into_iter().map(|(p, _)| p));
+            return digest;
+        }
+        let had_bye: HashSet<&PlayerId> = matches
+            .rounds
+            .values()
+            .filter(|r| r.is_bye)
+            .flat_map(|r| r.players.iter())
+            .collect();
+        let lowest = plyrs.iter().map(|(_, s)| *s).fold(f64::INFINITY, f64::min);
+        // Score differences dominate the cost and the difference in rank breaks ties
+        let cost = |i: usize, i_score: f64, j: usize, j_score: f64| -> i64 {
+            let diff = i_score - j_score;
+            (100.0 * diff * diff).round() as i64 * count as i64 + i.abs_diff(j) as i64
+        };
+        // The "bye" is an extra vertex that is ranked below everyone and has the lowest score
+        let bye = (count % 2 == 1).then_some(count);
+        let mut costs: Vec<(usize, usize, i64, bool)> = Vec::new();
+        for (i, (p_one, s_one)) in plyrs.iter().enumerate() {
+            for (j, (p_two, s_two)) in plyrs.iter().enumerate().skip(i + 1) {
+                let rematch = matches
+                    .opponents
+                    .get(p_one)
+                    .map(|opps| opps.contains(p_two))
+                    .unwrap_or_default();
+                costs.push((i, j, cost(i, *s_one, j, *s_two), rematch));
+            }
+            if let Some(b) = bye {
+                costs.push((i, b, cost(i, *s_one, b, lowest), had_bye.contains(p_one)));
+            }
+        }
+        // A single rematch must cost more than any set of pairings without one
+        let max_cost = costs
+            .iter()
+            .map(|(_, _, c, _)| *c)
+            .max()
+            .unwrap_or_default();
+        let penalty = max_cost * (count as i64 / 2 + 1) + 1;
+        let offset = max_cost + penalty + 1;
+        let edges: Vec<Edge> = costs
+            .into_iter()
+            .map(|(i, j, c, rematch)| (i, j, offset - c - if rematch { penalty } else { 0 }))
+            .collect();
+        let mates = max_weight_matching(&edges, true);
+        for (i, mate) in mates.into_iter().enumerate().take(count) {
+            match mate {
+                Some(j) if Some(j) == bye => {
+                    digest.rejected.push(plyrs[i].0.clone());
+                }
+                Some(j) if i < j => {
+                    let pairing = vec![plyrs[i].0.clone(), plyrs[j].0.clone()];
+                    digest.paired.push(balance_seats(pairing, matches));
+                }
+                Some(_) => {}
+                None => {
+                    digest.rejected.push(plyrs[i].0.clone());
+                }
+            }
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use std::fmt;
+
+    use super::*;
+    use crate::{operations::OpContext, player::Player, round::RoundResult};
+
+    struct Points(f64);
+
+    impl fmt::Display for Points {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl Score for Points {
+        fn primary_score(&self) -> f64 {
+            self.0
+        }
+    }
+
+    fn players(count: usize) -> Vec<PlayerId> {
+        (0..count)
+            .map(|i| Player::new(format!("Player {i}")).id)
+            .collect()
+    }
+
+    /// Creates a registry where each of the given groups played a match together. A group of one
+    /// player had a bye.
+    fn registry(groups: &[&[&PlayerId]]) -> RoundRegistry {
+        let mut reg = RoundRegistry::new(0, Duration::from_secs(0));
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        for group in groups {
+            let ident = reg.create_round(&mut ctx);
+            for plyr in group.iter() {
+                reg.add_player_to_round(&ident, (*plyr).clone()).unwrap();
+            }
+            if group.len() == 1 {
+                reg.get_mut_round(&ident).unwrap().record_bye().unwrap();
+            }
+        }
+        reg
+    }
+
+    fn scored(plyrs: &[PlayerId], scores: &[f64]) -> Vec<(PlayerId, f64)> {
+        plyrs.iter().cloned().zip(scores.iter().copied()).collect()
+    }
+
+    fn is_rematch(reg: &RoundRegistry, pairing: &[PlayerId]) -> bool {
+        reg.opponents
+            .get(&pairing[0])
+            .map(|opps| opps.contains(&pairing[1]))
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn closest_scores_are_paired() {
+        let plyrs = players(4);
+        let reg = registry(&[]);
+        let pairings =
+            SwissPairings::new(2).optimal_pair(&reg, scored(&plyrs, &[3.0, 3.0, 1.0, 1.0]));
+        assert!(pairings.rejected.is_empty());
+        assert_eq!(pairings.paired.len(), 2);
+        for pairing in &pairings.paired {
+            let mut pairing = pairing.clone();
+            pairing.sort_by_key(|p| plyrs.iter().position(|q| q == p));
+            assert!(pairing == plyrs[0..2] || pairing == plyrs[2..4]);
+        }
+    }
+
+    #[test]
+    fn rematches_are_avoided() {
+        let plyrs = players(4);
+        let reg = registry(&[&[&plyrs[0], &plyrs[1]], &[&plyrs[2], &plyrs[3]]]);
+        // The closest scores are the previous pairings, but there is a way to pair without them
+        let pairings =
+            SwissPairings::new(2).optimal_pair(&reg, scored(&plyrs, &[3.0, 3.0, 1.0, 1.0]));
+        assert!(pairings.rejected.is_empty());
+        assert_eq!(pairings.paired.len(), 2);
+        assert!(pairings.paired.iter().all(|p| !is_rematch(&reg, p)));
+    }
+
+    #[test]
+    fn unavoidable_rematches_are_minimized() {
+        let plyrs = players(4);
+        // Player 0 has played everyone except player 3, so the only way to avoid a rematch
+        // entirely would leave players 1 and 2 together, which they have also played
+        let reg = registry(&[
+            &[&plyrs[0], &plyrs[1]],
+            &[&plyrs[0], &plyrs[2]],
+            &[&plyrs[1], &plyrs[2]],
+        ]);
+        let pairings =
+            SwissPairings::new(2).optimal_pair(&reg, scored(&plyrs, &[2.0, 2.0, 2.0, 0.0]));
+        assert!(pairings.rejected.is_empty());
+        assert_eq!(pairings.paired.len(), 2);
+        let rematches = pairings
+            .paired
+            .iter()
+            .filter(|p| is_rematch(&reg, p))
+            .count();
+        assert_eq!(rematches, 1);
+
+        // Two players that have already played are still paired rather than left out
+        let reg = registry(&[&[&plyrs[0], &plyrs[1]]]);
+        let pairings = SwissPairings::new(2).optimal_pair(&reg, scored(&plyrs[..2], &[1.0, 0.0]));
+        assert!(pairings.rejected.is_empty());
+        assert_eq!(pairings.paired.len(), 1);
+    }
+
+    #[test]
+    fn lowest_ranked_player_gets_the_bye() {
+        let plyrs = players(5);
+        let reg = registry(&[]);
+        let pairings =
+            SwissPairings::new(2).optimal_pair(&reg, scored(&plyrs, &[3.0, 3.0, 2.0, 2.0, 2.0]));
+        assert_eq!(pairings.rejected, vec![plyrs[4].clone()]);
+        assert_eq!(pairings.paired.len(), 2);
+    }
+
+    #[test]
+    fn byes_are_not_repeated() {
+        let plyrs = players(5);
+        let reg = registry(&[&[&plyrs[4]], &[&plyrs[3]]]);
+        let pairings =
+            SwissPairings::new(2).optimal_pair(&reg, scored(&plyrs, &[3.0, 3.0, 2.0, 2.0, 2.0]));
+        assert_eq!(pairings.rejected, vec![plyrs[2].clone()]);
+        assert_eq!(pairings.paired.len(), 2);
+        assert!(pairings.paired.iter().all(|p| !is_rematch(&reg, p)));
+    }
+
+    #[test]
+    fn pairing_backtracks_until_the_retry_limit() {
+        let plyrs = players(4);
+        // The walk first tries the leader with player 1, who has played everyone else
+        let reg = registry(&[&[&plyrs[1], &plyrs[2]], &[&plyrs[1], &plyrs[3]]]);
+        let mut sys = SwissPairings::new(3);
+        assert_eq!(sys.find_pairing(&reg, &plyrs, 3), Some(vec![0, 2, 3]));
+        sys.update_setting(SwissPairingsSetting::RetryLimit(0));
+        assert_eq!(sys.find_pairing(&reg, &plyrs, 3), None);
+    }
+
+    #[test]
+    fn unpairable_fields_stop_at_the_retry_limit() {
+        // Everyone has played the rest of their group, so any pod of eight has a rematch. Without
+        // a limit, the walk would try every pod of seven with one player from each group.
+        let plyrs = players(56);
+        let groups: Vec<&[PlayerId]> = plyrs.chunks(8).collect();
+        let mut played: Vec<[&PlayerId; 2]> = Vec::new();
+        for group in &groups {
+            for (i, one) in group.iter().enumerate() {
+                for two in &group[i + 1..] {
+                    played.push([one, two]);
+                }
+            }
+        }
+        let played: Vec<&[&PlayerId]> = played.iter().map(|p| &p[..]).collect();
+        let reg = registry(&played);
+        // The field is ordered so that the walk picks one player from each group first
+        let order: Vec<PlayerId> = (0..8)
+            .flat_map(|i| groups.iter().map(move |g| g[i].clone()))
+            .collect();
+        let sys = SwissPairings::new(8);
+        let start = std::time::Instant::now();
+        assert_eq!(sys.find_pairing(&reg, &order, 8), None);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    /// Creates a field of four players where the leader has played everyone else
+    fn unpairable_leader() -> (PlayerRegistry, RoundRegistry, Vec<PlayerId>) {
+        let mut reg = PlayerRegistry::new();
+        let mut ctx = OpContext::new(Uuid::new_v4(), SystemTime::now());
+        let plyrs: Vec<PlayerId> = (0..4)
+            .map(|i| reg.add_player(format!("Player {i}"), &mut ctx).unwrap())
+            .collect();
+        let mut matches = registry(&[
+            &[&plyrs[0], &plyrs[1]],
+            &[&plyrs[0], &plyrs[2]],
+            &[&plyrs[0], &plyrs[3]],
+        ]);
+        for rnd in matches.rounds.values_mut() {
+            rnd.force_certify(vec![RoundResult::Wins(plyrs[0].clone(), 1)])
+                .unwrap();
+        }
+        (reg, matches, plyrs)
+    }
+
+    /// Standings are listed from lowest ranked to highest
+    fn ranked(plyrs: &[PlayerId]) -> Standings<Points> {
+        Standings::new(
+            plyrs
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(i, p)| (p.clone(), Points(i as f64)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn unpairable_leader_is_rejected() {
+        let (reg, matches, plyrs) = unpairable_leader();
+        let pairings = SwissPairings::new(2)
+            .pair(&reg, &matches, ranked(&plyrs))
+            .unwrap();
+        // Leaving out the leader leaves an odd number of players, so the lowest is also left out
+        assert_eq!(pairings.rejected, vec![plyrs[0].clone(), plyrs[3].clone()]);
+        assert_eq!(pairings.paired.len(), 1);
+        assert!(pairings.paired[0].contains(&plyrs[1]));
+        assert!(pairings.paired[0].contains(&plyrs[2]));
+    }
+
+    #[test]
+    fn unpairable_leader_is_paired_as_a_last_resort() {
+        let (reg, matches, plyrs) = unpairable_leader();
+        let mut sys = SwissPairings::new(2);
+        sys.update_setting(SwissPairingsSetting::AllowRematches(true));
+        let pairings = sys.pair(&reg, &matches, ranked(&plyrs)).unwrap();
+        assert!(pairings.rejected.is_empty());
+        assert_eq!(pairings.paired.len(), 2);
+        let leader = pairings
+            .paired
+            .iter()
+            .find(|p| p.contains(&plyrs[0]))
+            .unwrap();
+        assert!(leader.contains(&plyrs[1]));
+    }
 }
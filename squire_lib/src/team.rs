@@ -0,0 +1,61 @@
+use crate::{
+    operations::OpContext,
+    player::{Player, PlayerId},
+};
+
+use serde::{Deserialize, Serialize};
+
+use std::hash::{Hash, Hasher};
+
+/// A group of players that is registered, paired, and scored as one unit, e.g. a Two-Headed Giant
+/// team. The team itself is a player in the player registry, which is what pairing and scoring
+/// systems see. The members are not in the registry and are only used to track their decks and
+/// seats.
+///
+/// The order of the members is their seating order. In a match between two teams, the members in
+/// the same seat play each other, e.g. seat A vs. seat A.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Team {
+    pub id: PlayerId,
+    pub members: Vec<Player>,
+}
+
+impl Team {
+    pub fn new(id: PlayerId, members: Vec<String>, ctx: &mut OpContext) -> Self {
+        Team {
+            id,
+            members: members
+                .into_iter()
+                .map(|name| Player::with_id(ctx.next_id(), name))
+                .collect(),
+        }
+    }
+
+    pub fn get_member(&self, seat: usize) -> Option<&Player> {
+        self.members.get(seat)
+    }
+
+    pub fn get_mut_member(&mut self, seat: usize) -> Option<&mut Player> {
+        self.members.get_mut(seat)
+    }
+
+    /// Gets the seat (starting from 0) of the member with the given id
+    pub fn get_seat(&self, id: &PlayerId) -> Option<usize> {
+        self.members.iter().position(|p| &p.id == id)
+    }
+}
+
+impl Hash for Team {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        let _ = &self.id.hash(state);
+    }
+}
+
+impl PartialEq for Team {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
+    cmp::Reverse,
     collections::HashMap,
     ffi::{CStr, CString},
+    fmt,
     hash::{Hash, Hasher},
     str::Utf8Error,
     time::Duration,
@@ -12,14 +14,20 @@ use uuid::Uuid;
 use mtgjson::model::deck::Deck;
 
 use crate::{
+    accounts::{AccountId, UserAccount},
+    chess_scoring::{BuchholzScoring, ChessScore, CumulativeScoring, SonnebornBergerScoring},
+    elimination_pairings::EliminationPairings,
     error::TournamentError,
     fluid_pairings::FluidPairings,
-    operations::{OpData, OpResult, TournOp},
+    match_slips::MatchSlips,
+    operations::{OpAuthor, OpContext, OpData, OpResult, OpRole, TournOp},
     pairings::Pairings,
+    placement_scoring::{PlacementScore, PlacementScoring},
     player::{Player, PlayerId, PlayerStatus},
     player_registry::{PlayerIdentifier, PlayerRegistry},
     round::{Round, RoundId, RoundResult, RoundStatus},
     round_registry::{RoundIdentifier, RoundRegistry},
+    round_robin_pairings::RoundRobinPairings,
     scoring::{Score, Standings},
     settings::{
         self, FluidPairingsSetting, PairingSetting, ScoringSetting, StandardScoringSetting,
@@ -27,6 +35,7 @@ use crate::{
     },
     standard_scoring::{StandardScore, StandardScoring},
     swiss_pairings::SwissPairings,
+    team::Team,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,17 +43,71 @@ use crate::{
 pub enum TournamentPreset {
     Swiss,
     Fluid,
+    RoundRobin,
+    SingleElimination,
+    DoubleElimination,
+}
+
+/// The scoring systems that a tournament can switch to. Each system starts with its default
+/// settings.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum ScoringPreset {
+    Standard,
+    Buchholz,
+    SonnebornBerger,
+    Cumulative,
+    Placement,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ScoringSystem {
     Standard(StandardScoring),
+    Buchholz(BuchholzScoring),
+    SonnebornBerger(SonnebornBergerScoring),
+    Cumulative(CumulativeScoring),
+    Placement(PlacementScoring),
+}
+
+/// A score from any of the scoring systems
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum TournamentScore {
+    Standard(StandardScore),
+    Chess(ChessScore),
+    Placement(PlacementScore),
+}
+
+/// The standings after a round of pairings was fully certified
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StandingsSnapshot {
+    /// The number of rounds of pairings that had been certified, starting from 1
+    pub round: usize,
+    pub standings: Standings<TournamentScore>,
+}
+
+/// A player's place in the standings after a round of pairings
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RankHistoryEntry {
+    pub round: usize,
+    pub rank: usize,
+    pub score: TournamentScore,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum PairingSystem {
     Swiss(SwissPairings),
     Fluid(FluidPairings),
+    RoundRobin(RoundRobinPairings),
+    Elimination(EliminationPairings),
+}
+
+/// A stage of a tournament, e.g. the Swiss rounds or the top cut. Each phase has its own pairing
+/// system, scoring system, and round length.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TournamentPhase {
+    pub pairing_sys: PairingSystem,
+    pub scoring_sys: ScoringSystem,
+    pub round_length: Duration,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -74,16 +137,42 @@ pub struct Tournament {
     pub use_table_number: bool,
     pub format: String,
     pub game_size: u8,
+    /// The number of players on each team. Players are registered individually if this is 1.
+    #[serde(default = "default_team_size")]
+    pub team_size: u8,
+    /// The number of extra turns that a round gets after time is called
+    #[serde(default = "default_extra_turns")]
+    pub extra_turns: u8,
     pub min_deck_count: u8,
     pub max_deck_count: u8,
     pub player_reg: PlayerRegistry,
     pub round_reg: RoundRegistry,
     pub pairing_sys: PairingSystem,
     pub scoring_sys: ScoringSystem,
+    /// The phases that will be played after the current one, in order
+    #[serde(default)]
+    pub phases: Vec<TournamentPhase>,
+    /// The phases that have been played, in order
+    #[serde(default)]
+    pub completed_phases: Vec<TournamentPhase>,
+    /// The players in the final standings of the previous phase, from best to worst
+    #[serde(default)]
+    pub seeding: Vec<PlayerId>,
+    /// The match number of the first round of the current phase
+    #[serde(default)]
+    pub(crate) phase_start: u64,
+    /// The rounds made by each round of pairings that hasn't been fully certified, oldest first
+    #[serde(default)]
+    pub(crate) pending_pairings: Vec<Vec<RoundId>>,
+    /// The standings after each round of pairings was fully certified, oldest first
+    #[serde(default)]
+    pub standings_history: Vec<StandingsSnapshot>,
     pub reg_open: bool,
     pub require_check_in: bool,
     pub require_deck_reg: bool,
     pub status: TournamentStatus,
+    pub judges: HashMap<AccountId, UserAccount>,
+    pub admins: HashMap<AccountId, UserAccount>,
 }
 
 impl Tournament {
@@ -94,20 +183,32 @@ impl Tournament {
             use_table_number: true,
             format,
             game_size: 2,
+            team_size: 1,
+            extra_turns: 5,
             min_deck_count: 1,
             max_deck_count: 2,
             player_reg: PlayerRegistry::new(),
             round_reg: RoundRegistry::new(0, Duration::from_secs(3000)),
             pairing_sys: pairing_system_factory(&preset, 2),
             scoring_sys: scoring_system_factory(&preset),
+            phases: Vec::new(),
+            completed_phases: Vec::new(),
+            seeding: Vec::new(),
+            phase_start: 0,
+            pending_pairings: Vec::new(),
+            standings_history: Vec::new(),
             reg_open: true,
             require_check_in: false,
             require_deck_reg: false,
             status: TournamentStatus::Planned,
+            judges: HashMap::new(),
+            admins: HashMap::new(),
         }
     }
 
-    pub fn apply_op(&mut self, op: TournOp) -> OpResult {
+    /// Applies an operation to the tournament. Everything that the operation creates takes its
+    /// time and id from the given context.
+    pub fn apply_op(&mut self, op: TournOp, mut ctx: OpContext) -> OpResult {
         use TournOp::*;
         match op {
             UpdateReg(b) => self.update_reg(b),
@@ -117,27 +218,100 @@ impl Tournament {
             End() => self.end(),
             Cancel() => self.cancel(),
             CheckIn(p_ident) => self.check_in(&p_ident),
-            RegisterPlayer(name) => self.register_player(name),
+            RegisterPlayer(name) => self.register_player(name, &mut ctx),
             RecordResult(r_ident, result) => self.record_result(&r_ident, result),
             ConfirmResult(p_ident) => self.confirm_round(&p_ident),
+            DisputeResult(p_ident) => self.dispute_round(&p_ident),
+            CertifyRound(r_ident, results) => self.certify_round(&r_ident, results),
             DropPlayer(p_ident) => self.drop_player(&p_ident),
             AdminDropPlayer(p_ident) => self.admin_drop_player(&p_ident),
             AddDeck(p_ident, name, deck) => self.player_add_deck(&p_ident, name, deck),
             RemoveDeck(p_ident, name) => self.remove_player_deck(&p_ident, name),
             RemoveRound(r_ident) => self.remove_round(&r_ident),
             SetGamerTag(p_ident, tag) => self.player_set_game_name(&p_ident, tag),
-            ReadyPlayer(p_ident) => self.ready_player(&p_ident),
+            ReadyPlayer(p_ident) => self.ready_player(&p_ident, &mut ctx),
             UnReadyPlayer(p_ident) => self.unready_player(&p_ident),
             UpdateTournSetting(setting) => self.update_setting(setting),
-            GiveBye(p_ident) => self.give_bye(&p_ident),
-            CreateRound(p_idents) => self.create_round(p_idents),
-            PairRound() => self.pair(),
+            GiveBye(p_ident) => self.give_bye(&p_ident, &mut ctx),
+            CreateRound(p_idents) => self.create_round(p_idents, &mut ctx),
+            PairRound() => self.pair(&mut ctx),
             TimeExtension(rnd, ext) => self.give_time_extension(&rnd, ext),
+            PauseRound(rnd) => self.pause_round(&rnd, &ctx),
+            ResumeRound(rnd) => self.resume_round(&rnd, &ctx),
+            CallTime(rnd) => self.call_time(&rnd, &ctx),
+            TakeExtraTurn(rnd) => self.take_extra_turn(&rnd),
             Cut(n) => self.cut_to_top(n),
             PruneDecks() => self.prune_decks(),
             PrunePlayers() => self.prune_players(),
             ImportPlayer(plyr) => self.import_player(plyr),
             ImportRound(rnd) => self.import_round(rnd),
+            RegisterJudge(account) => self.register_judge(account),
+            RegisterAdmin(account) => self.register_admin(account),
+            AddPhase(preset, len) => self.add_phase(preset, len),
+            NextPhase() => self.next_phase(),
+            RegisterTeam(name, members) => self.register_team(name, members, &mut ctx),
+            AddMemberDeck(p_ident, seat, name, deck) => {
+                self.member_add_deck(&p_ident, seat, name, deck)
+            }
+            RemoveMemberDeck(p_ident, seat, name) => self.remove_member_deck(&p_ident, seat, name),
+            RegisterPlayerAccount(account) => self.register_account_player(account, &mut ctx),
+        }
+    }
+
+    /// Checks if the given author is allowed to submit the given operation. The system can submit
+    /// any operation. Judges and admins must be registered with the tournament. Players can only
+    /// submit operations that act on themselves.
+    pub fn check_authority(&self, author: &OpAuthor, op: &TournOp) -> Result<(), TournamentError> {
+        let allowed = match author {
+            OpAuthor::System => true,
+            OpAuthor::Admin(id) => self.admins.contains_key(id),
+            OpAuthor::Judge(id) => {
+                op.required_role() != OpRole::Admin
+                    && (self.judges.contains_key(id) || self.admins.contains_key(id))
+            }
+            OpAuthor::Player(id) => op.required_role() == OpRole::Player && self.acts_on(id, op),
+            OpAuthor::Account(id) => {
+                matches!(op, TournOp::RegisterPlayerAccount(acc) if &acc.external_id == id)
+            }
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(TournamentError::InsufficientPermissions)
+        }
+    }
+
+    /// Gets the author that operations submitted by the given account are recorded under. Admins
+    /// are preferred over judges, and judges over players. Accounts that have no role in the
+    /// tournament can only register themselves.
+    pub fn author_for(&self, account: &AccountId) -> OpAuthor {
+        if self.admins.contains_key(account) {
+            OpAuthor::Admin(account.clone())
+        } else if self.judges.contains_key(account) {
+            OpAuthor::Judge(account.clone())
+        } else if let Some(id) = self.player_reg.get_account_player(account) {
+            OpAuthor::Player(id)
+        } else {
+            OpAuthor::Account(account.clone())
+        }
+    }
+
+    /// Checks if the given account is a judge or admin of the tournament
+    pub fn is_staff(&self, account: &AccountId) -> bool {
+        self.judges.contains_key(account) || self.admins.contains_key(account)
+    }
+
+    /// Determines if the given player operation only acts on the given player
+    fn acts_on(&self, id: &PlayerId, op: &TournOp) -> bool {
+        if let Some(ident) = op.get_player_ident() {
+            self.player_reg.get_player_id(&ident).as_ref() == Some(id)
+        } else if let Some(ident) = op.get_match_ident() {
+            self.round_reg
+                .get_round(&ident)
+                .map(|r| r.players.contains(id))
+                .unwrap_or(false)
+        } else {
+            false
         }
     }
 
@@ -164,6 +338,45 @@ impl Tournament {
         }
     }
 
+    pub fn get_team(&self, ident: &PlayerIdentifier) -> Result<Team, TournamentError> {
+        match self.player_reg.get_team(ident) {
+            Some(team) => Ok(team.clone()),
+            None => Err(TournamentError::PlayerLookup),
+        }
+    }
+
+    /// Gets who plays who in a round between teams. Members in the same seat play each other, so
+    /// the `i`th entry holds the members in seat `i` of each team, in the round's seat order.
+    pub fn get_round_matchups(
+        &self,
+        ident: &RoundIdentifier,
+    ) -> Result<Vec<Vec<PlayerId>>, TournamentError> {
+        let round = self
+            .round_reg
+            .get_round(ident)
+            .ok_or(TournamentError::RoundLookup)?;
+        let teams = round
+            .players
+            .iter()
+            .map(|id| self.player_reg.teams.get(id))
+            .collect::<Option<Vec<&Team>>>()
+            .ok_or(TournamentError::PlayerLookup)?;
+        let seats = teams
+            .iter()
+            .map(|t| t.members.len())
+            .max()
+            .unwrap_or_default();
+        Ok((0..seats)
+            .map(|seat| {
+                teams
+                    .iter()
+                    .filter_map(|t| t.get_member(seat))
+                    .map(|p| p.id.clone())
+                    .collect()
+            })
+            .collect())
+    }
+
     pub fn get_round(&self, ident: &RoundIdentifier) -> Result<Round, TournamentError> {
         match self.round_reg.get_round(ident) {
             Some(rnd) => Ok(rnd.clone()),
@@ -171,6 +384,14 @@ impl Tournament {
         }
     }
 
+    /// Gets the pairings and result slips for the given rounds, e.g. the rounds from a pairing
+    pub fn get_match_slips(
+        &self,
+        rounds: &[RoundIdentifier],
+    ) -> Result<MatchSlips, TournamentError> {
+        MatchSlips::new(self, rounds)
+    }
+
     pub fn get_player_rounds(
         &self,
         ident: &PlayerIdentifier,
@@ -182,11 +403,23 @@ impl Tournament {
         Ok(self
             .round_reg
             .rounds
-            .iter()
-            .filter_map(|(_, r)| r.players.get(&id).map(|_| r.clone()))
+            .values()
+            .filter(|r| r.players.contains(&id))
+            .cloned()
             .collect())
     }
 
+    pub fn get_player_seat_history(
+        &self,
+        ident: &PlayerIdentifier,
+    ) -> Result<Vec<usize>, TournamentError> {
+        let id = self
+            .player_reg
+            .get_player_id(ident)
+            .ok_or(TournamentError::PlayerLookup)?;
+        Ok(self.round_reg.get_seat_history(&id))
+    }
+
     pub fn get_player_deck(
         &self,
         ident: &PlayerIdentifier,
@@ -202,9 +435,135 @@ impl Tournament {
         }
     }
 
-    pub fn get_standings(&self) -> Standings<StandardScore> {
+    /// Gets the standings of the current phase. Rounds from earlier phases aren't counted.
+    pub fn get_standings(&self) -> Standings<TournamentScore> {
         self.scoring_sys
-            .get_standings(&self.player_reg, &self.round_reg)
+            .get_standings(&self.player_reg, &self.round_reg, self.phase_start)
+    }
+
+    /// Checks that the running totals kept by the scoring system match a full recount of every
+    /// round
+    pub fn verify_standings(&self) -> bool {
+        self.scoring_sys
+            .verify_cache(&self.round_reg, self.phase_start)
+    }
+
+    /// Updates the scoring system after a round is certified, killed, or has its results changed
+    fn rescore_round(&mut self, ident: &RoundIdentifier) {
+        if let Some(rnd) = self.round_reg.get_round(ident) {
+            self.scoring_sys
+                .update_round(&self.round_reg, rnd, self.phase_start);
+        }
+        self.snapshot_standings();
+    }
+
+    /// Gets a player's rank and score after each round of pairings. Rounds where the player
+    /// wasn't in the standings, e.g. before they registered, are skipped.
+    pub fn get_rank_history(
+        &self,
+        ident: &PlayerIdentifier,
+    ) -> Result<Vec<RankHistoryEntry>, TournamentError> {
+        let id = self
+            .player_reg
+            .get_player_id(ident)
+            .ok_or(TournamentError::PlayerLookup)?;
+        let digest = self
+            .standings_history
+            .iter()
+            .filter_map(|snapshot| {
+                snapshot
+                    .standings
+                    .scores
+                    .iter()
+                    .find(|(p, _)| p == &id)
+                    .map(|(_, score)| RankHistoryEntry {
+                        round: snapshot.round,
+                        rank: score.rank(),
+                        score: score.clone(),
+                    })
+            })
+            .collect();
+        Ok(digest)
+    }
+
+    /// Tracks a new round of pairings so that the standings can be recorded once it is certified
+    fn add_pending_pairings(&mut self, rounds: &[RoundIdentifier]) {
+        let ids = rounds
+            .iter()
+            .filter_map(|r| self.round_reg.get_round_id(r))
+            .collect();
+        self.pending_pairings.push(ids);
+        // The pairings might be made up of only byes
+        self.snapshot_standings();
+    }
+
+    /// Records the standings for each round of pairings that has been fully certified. Rounds
+    /// that were killed don't hold up the rest of their pairings.
+    fn snapshot_standings(&mut self) {
+        let round_reg = &self.round_reg;
+        let mut certified = 0;
+        self.pending_pairings.retain(|ids| {
+            let rounds: Vec<&Round> = ids
+                .iter()
+                .filter_map(|id| round_reg.get_round(&RoundIdentifier::Id(id.clone())))
+                .collect();
+            let done = rounds
+                .iter()
+                .all(|r| r.is_certified() || r.status == RoundStatus::Dead);
+            if done && rounds.iter().any(|r| r.is_certified()) {
+                certified += 1;
+            }
+            !done
+        });
+        if certified == 0 {
+            return;
+        }
+        let standings = self.get_standings();
+        for _ in 0..certified {
+            self.standings_history.push(StandingsSnapshot {
+                round: self.standings_history.len() + 1,
+                standings: standings.clone(),
+            });
+        }
+    }
+
+    /// Gets the rounds that have run out of time but haven't had time called, so that time can be
+    /// called in them
+    pub fn get_expired_rounds(&self) -> Vec<RoundIdentifier> {
+        let mut digest: Vec<&Round> = self
+            .round_reg
+            .rounds
+            .values()
+            .filter(|r| r.is_expired())
+            .collect();
+        digest.sort_by_key(|r| r.match_number);
+        digest
+            .into_iter()
+            .map(|r| RoundIdentifier::Id(r.id.clone()))
+            .collect()
+    }
+
+    /// Gets the index of the current phase, starting from 0
+    pub fn current_phase(&self) -> usize {
+        self.completed_phases.len()
+    }
+
+    /// Gets the standings used to pair the current phase. Until the first round of the phase is
+    /// created, players are ordered by the seeding from the previous phase.
+    fn get_phase_standings(&self) -> Standings<TournamentScore> {
+        let mut standings = self.get_standings();
+        if !self.seeding.is_empty() && self.round_reg.rounds.keys().all(|n| *n < self.phase_start) {
+            // Standings are sorted from worst to best, so unseeded players go first
+            standings.scores.sort_by_key(|(p, _)| {
+                Reverse(
+                    self.seeding
+                        .iter()
+                        .position(|s| s == p)
+                        .unwrap_or(usize::MAX),
+                )
+            });
+        }
+        standings
     }
 
     pub(crate) fn prune_decks(&mut self) -> OpResult {
@@ -212,7 +571,12 @@ impl Tournament {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
         if self.require_deck_reg {
-            for (_, p) in self.player_reg.players.iter_mut() {
+            let members = self
+                .player_reg
+                .teams
+                .values_mut()
+                .flat_map(|t| t.members.iter_mut());
+            for p in self.player_reg.players.values_mut().chain(members) {
                 while p.decks.len() > self.max_deck_count as usize {
                     let name = p.deck_ordering[0].clone();
                     let _ = p.remove_deck(name);
@@ -234,7 +598,25 @@ impl Tournament {
         if !(self.is_planned() || self.is_active()) {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
+        let ident = RoundIdentifier::Id(rnd.id.clone());
         self.round_reg.import_round(rnd)?;
+        self.rescore_round(&ident);
+        Ok(OpData::Nothing)
+    }
+
+    pub(crate) fn register_judge(&mut self, account: UserAccount) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.judges.insert(account.external_id.clone(), account);
+        Ok(OpData::Nothing)
+    }
+
+    pub(crate) fn register_admin(&mut self, account: UserAccount) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.admins.insert(account.external_id.clone(), account);
         Ok(OpData::Nothing)
     }
 
@@ -243,8 +625,16 @@ impl Tournament {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
         if self.require_deck_reg {
-            for (_, p) in self.player_reg.players.iter_mut() {
-                if p.decks.len() < self.min_deck_count as usize {
+            for (id, p) in self.player_reg.players.iter_mut() {
+                // The decks of a team are registered by its members
+                let too_few = match self.player_reg.teams.get(id) {
+                    Some(team) => team
+                        .members
+                        .iter()
+                        .any(|m| m.decks.len() < self.min_deck_count as usize),
+                    None => p.decks.len() < self.min_deck_count as usize,
+                };
+                if too_few {
                     p.update_status(PlayerStatus::Dropped);
                 }
             }
@@ -271,6 +661,50 @@ impl Tournament {
         Ok(OpData::Nothing)
     }
 
+    pub(crate) fn pause_round(&mut self, rnd: &RoundIdentifier, ctx: &OpContext) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg
+            .get_mut_round(rnd)
+            .ok_or(TournamentError::RoundLookup)?
+            .pause(ctx.time)?;
+        Ok(OpData::Nothing)
+    }
+
+    pub(crate) fn resume_round(&mut self, rnd: &RoundIdentifier, ctx: &OpContext) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg
+            .get_mut_round(rnd)
+            .ok_or(TournamentError::RoundLookup)?
+            .resume(ctx.time)?;
+        Ok(OpData::Nothing)
+    }
+
+    pub(crate) fn call_time(&mut self, rnd: &RoundIdentifier, ctx: &OpContext) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg
+            .get_mut_round(rnd)
+            .ok_or(TournamentError::RoundLookup)?
+            .call_time(ctx.time, self.extra_turns)?;
+        Ok(OpData::Nothing)
+    }
+
+    pub(crate) fn take_extra_turn(&mut self, rnd: &RoundIdentifier) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg
+            .get_mut_round(rnd)
+            .ok_or(TournamentError::RoundLookup)?
+            .take_extra_turn()?;
+        Ok(OpData::Nothing)
+    }
+
     pub(crate) fn check_in(&mut self, plyr: &PlayerIdentifier) -> OpResult {
         if !(self.is_planned() || self.is_active()) {
             return Err(TournamentError::IncorrectStatus(self.status));
@@ -287,34 +721,34 @@ impl Tournament {
         }
     }
 
-    pub(crate) fn pair(&mut self) -> OpResult {
+    pub(crate) fn pair(&mut self, ctx: &mut OpContext) -> OpResult {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
-        let standings = self
-            .scoring_sys
-            .get_standings(&self.player_reg, &self.round_reg);
+        let standings = self.get_phase_standings();
         if let Some(pairings) = self
             .pairing_sys
             .pair(&self.player_reg, &self.round_reg, standings)
         {
             let mut rounds = Vec::with_capacity(pairings.paired.len());
             for pair in pairings.paired {
-                let r_id = self.round_reg.create_round();
+                let r_id = self.round_reg.create_round(ctx);
                 for plyr in pair {
                     let _ = self.round_reg.add_player_to_round(&r_id, plyr);
                 }
                 rounds.push(r_id);
             }
-            if let PairingSystem::Swiss(_) = &self.pairing_sys {
+            if let PairingSystem::Swiss(_) | PairingSystem::RoundRobin(_) = &self.pairing_sys {
                 for plyr in pairings.rejected {
-                    let r_id = self.round_reg.create_round();
+                    let r_id = self.round_reg.create_round(ctx);
                     let rnd = self.round_reg.get_mut_round(&r_id).unwrap();
                     rnd.add_player(plyr);
                     let _ = rnd.record_bye();
+                    self.rescore_round(&r_id);
                     rounds.push(r_id);
                 }
             }
+            self.add_pending_pairings(&rounds);
             Ok(OpData::Pair(rounds))
         } else {
             Ok(OpData::Nothing)
@@ -326,6 +760,7 @@ impl Tournament {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
         self.round_reg.kill_round(ident)?;
+        self.rescore_round(ident);
         Ok(OpData::Nothing)
     }
 
@@ -356,6 +791,25 @@ impl Tournament {
             RequireDeckReg(b) => {
                 self.require_deck_reg = b;
             }
+            // Everyone registers the same way, so the team size is fixed once anyone has
+            TeamSize(s) => {
+                if !self.is_planned() {
+                    return Err(TournamentError::IncorrectStatus(self.status));
+                }
+                if s == 0 || !self.player_reg.is_empty() {
+                    return Err(TournamentError::IncorrectTeamSize);
+                }
+                self.team_size = s;
+            }
+            ExtraTurns(n) => {
+                self.extra_turns = n;
+            }
+            BestOf(n) => {
+                self.round_reg.best_of = n;
+            }
+            ScoringPreset(preset) => {
+                self.scoring_sys = ScoringSystem::from_preset(preset);
+            }
             PairingSetting(setting) => match setting {
                 settings::PairingSetting::Swiss(s) => {
                     if let PairingSystem::Swiss(sys) = &mut self.pairing_sys {
@@ -371,6 +825,13 @@ impl Tournament {
                         return Err(TournamentError::IncompatiblePairingSystem);
                     }
                 }
+                settings::PairingSetting::Elimination(s) => {
+                    if let PairingSystem::Elimination(sys) = &mut self.pairing_sys {
+                        sys.update_setting(s);
+                    } else {
+                        return Err(TournamentError::IncompatiblePairingSystem);
+                    }
+                }
             },
             ScoringSetting(setting) => match setting {
                 settings::ScoringSetting::Standard(s) => {
@@ -380,6 +841,34 @@ impl Tournament {
                         return Err(TournamentError::IncompatibleScoringSystem);
                     }
                 }
+                settings::ScoringSetting::Buchholz(s) => {
+                    if let ScoringSystem::Buchholz(sys) = &mut self.scoring_sys {
+                        sys.update_setting(s);
+                    } else {
+                        return Err(TournamentError::IncompatibleScoringSystem);
+                    }
+                }
+                settings::ScoringSetting::SonnebornBerger(s) => {
+                    if let ScoringSystem::SonnebornBerger(sys) = &mut self.scoring_sys {
+                        sys.update_setting(s);
+                    } else {
+                        return Err(TournamentError::IncompatibleScoringSystem);
+                    }
+                }
+                settings::ScoringSetting::Cumulative(s) => {
+                    if let ScoringSystem::Cumulative(sys) = &mut self.scoring_sys {
+                        sys.update_setting(s);
+                    } else {
+                        return Err(TournamentError::IncompatibleScoringSystem);
+                    }
+                }
+                settings::ScoringSetting::Placement(s) => {
+                    if let ScoringSystem::Placement(sys) = &mut self.scoring_sys {
+                        sys.update_setting(s);
+                    } else {
+                        return Err(TournamentError::IncompatibleScoringSystem);
+                    }
+                }
             },
         }
         Ok(OpData::Nothing)
@@ -442,14 +931,54 @@ impl Tournament {
         }
     }
 
-    pub(crate) fn register_player(&mut self, name: String) -> OpResult {
+    pub(crate) fn register_player(&mut self, name: String, ctx: &mut OpContext) -> OpResult {
         if !(self.is_active() || self.is_planned()) {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
         if !self.reg_open {
             return Err(TournamentError::RegClosed);
         }
-        let id = self.player_reg.add_player(name)?;
+        if self.team_size > 1 {
+            return Err(TournamentError::IncorrectTeamSize);
+        }
+        let id = self.player_reg.add_player(name, ctx)?;
+        Ok(OpData::RegisterPlayer(PlayerIdentifier::Id(id)))
+    }
+
+    pub(crate) fn register_account_player(
+        &mut self,
+        account: UserAccount,
+        ctx: &mut OpContext,
+    ) -> OpResult {
+        if !(self.is_active() || self.is_planned()) {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if !self.reg_open {
+            return Err(TournamentError::RegClosed);
+        }
+        if self.team_size > 1 {
+            return Err(TournamentError::IncorrectTeamSize);
+        }
+        let id = self.player_reg.add_account_player(&account, ctx)?;
+        Ok(OpData::RegisterPlayer(PlayerIdentifier::Id(id)))
+    }
+
+    pub(crate) fn register_team(
+        &mut self,
+        name: String,
+        members: Vec<String>,
+        ctx: &mut OpContext,
+    ) -> OpResult {
+        if !(self.is_active() || self.is_planned()) {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if !self.reg_open {
+            return Err(TournamentError::RegClosed);
+        }
+        if self.team_size < 2 || members.len() != self.team_size as usize {
+            return Err(TournamentError::IncorrectTeamSize);
+        }
+        let id = self.player_reg.add_team(name, members, ctx)?;
         Ok(OpData::RegisterPlayer(PlayerIdentifier::Id(id)))
     }
 
@@ -466,6 +995,7 @@ impl Tournament {
             .get_mut_round(ident)
             .ok_or(TournamentError::RoundLookup)?;
         round.record_result(result)?;
+        self.rescore_round(ident);
         Ok(OpData::Nothing)
     }
 
@@ -478,27 +1008,90 @@ impl Tournament {
             .get_player_id(ident)
             .ok_or(TournamentError::PlayerLookup)?;
         let round = self.round_reg.get_player_active_round(&id)?;
+        let r_id = RoundIdentifier::Id(round.id.clone());
         let status = round.confirm_round(id)?;
+        self.rescore_round(&r_id);
+        if let PairingSystem::Elimination(sys) = &mut self.pairing_sys {
+            sys.advance(&self.player_reg, &self.round_reg);
+        }
         Ok(OpData::ConfirmResult(status))
     }
 
+    pub(crate) fn dispute_round(&mut self, ident: &PlayerIdentifier) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        let id = self
+            .player_reg
+            .get_player_id(ident)
+            .ok_or(TournamentError::PlayerLookup)?;
+        self.round_reg
+            .get_player_active_round(&id)?
+            .dispute_round(id)?;
+        Ok(OpData::Nothing)
+    }
+
+    pub(crate) fn certify_round(
+        &mut self,
+        ident: &RoundIdentifier,
+        results: Vec<RoundResult>,
+    ) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg
+            .get_mut_round(ident)
+            .ok_or(TournamentError::RoundLookup)?
+            .force_certify(results)?;
+        self.rescore_round(ident);
+        if let PairingSystem::Elimination(sys) = &mut self.pairing_sys {
+            sys.advance(&self.player_reg, &self.round_reg);
+        }
+        Ok(OpData::Nothing)
+    }
+
+    /// A player can only drop themself between matches and while the tournament isn't frozen
     pub(crate) fn drop_player(&mut self, ident: &PlayerIdentifier) -> OpResult {
-        if self.is_dead() {
+        if self.is_dead() || self.is_frozen() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
+        let id = self
+            .player_reg
+            .get_player_id(ident)
+            .ok_or(TournamentError::PlayerLookup)?;
+        if self.round_reg.get_player_active_round(&id).is_ok() {
+            return Err(TournamentError::ActiveMatches);
+        }
         self.player_reg
             .remove_player(ident)
             .ok_or(TournamentError::PlayerLookup)?;
+        if let PairingSystem::Elimination(sys) = &mut self.pairing_sys {
+            sys.advance(&self.player_reg, &self.round_reg);
+        }
         Ok(OpData::Nothing)
     }
 
+    /// Admins can drop a player at any point, including while the tournament is frozen. If the
+    /// player is in a match, they forfeit it.
     pub(crate) fn admin_drop_player(&mut self, ident: &PlayerIdentifier) -> OpResult {
         if self.is_dead() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
+        let id = self
+            .player_reg
+            .get_player_id(ident)
+            .ok_or(TournamentError::PlayerLookup)?;
+        if let Ok(round) = self.round_reg.get_player_active_round(&id) {
+            let r_id = RoundIdentifier::Id(round.id.clone());
+            round.forfeit(&id)?;
+            self.rescore_round(&r_id);
+        }
         self.player_reg
             .remove_player(ident)
             .ok_or(TournamentError::PlayerLookup)?;
+        if let PairingSystem::Elimination(sys) = &mut self.pairing_sys {
+            sys.advance(&self.player_reg, &self.round_reg);
+        }
         Ok(OpData::Nothing)
     }
 
@@ -522,6 +1115,46 @@ impl Tournament {
         Ok(OpData::Nothing)
     }
 
+    pub(crate) fn member_add_deck(
+        &mut self,
+        ident: &PlayerIdentifier,
+        seat: usize,
+        name: String,
+        deck: Deck,
+    ) -> OpResult {
+        if !(self.is_planned() || self.is_active()) {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if !self.reg_open {
+            return Err(TournamentError::RegClosed);
+        }
+        let plyr = self
+            .player_reg
+            .get_mut_team(ident)
+            .and_then(|t| t.get_mut_member(seat))
+            .ok_or(TournamentError::PlayerLookup)?;
+        plyr.add_deck(name, deck);
+        Ok(OpData::Nothing)
+    }
+
+    pub(crate) fn remove_member_deck(
+        &mut self,
+        ident: &PlayerIdentifier,
+        seat: usize,
+        name: String,
+    ) -> OpResult {
+        if !(self.is_planned() || self.is_active()) {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        let plyr = self
+            .player_reg
+            .get_mut_team(ident)
+            .and_then(|t| t.get_mut_member(seat))
+            .ok_or(TournamentError::PlayerLookup)?;
+        plyr.remove_deck(name)?;
+        Ok(OpData::Nothing)
+    }
+
     pub(crate) fn get_player_decks(
         &self,
         ident: &PlayerIdentifier,
@@ -565,7 +1198,11 @@ impl Tournament {
         Ok(OpData::Nothing)
     }
 
-    pub(crate) fn ready_player(&mut self, ident: &PlayerIdentifier) -> OpResult {
+    pub(crate) fn ready_player(
+        &mut self,
+        ident: &PlayerIdentifier,
+        ctx: &mut OpContext,
+    ) -> OpResult {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
@@ -578,23 +1215,26 @@ impl Tournament {
             self.pairing_sys.ready_player(plyr.id.clone());
             should_pair = match &self.pairing_sys {
                 PairingSystem::Fluid(sys) => sys.ready_to_pair(),
-                PairingSystem::Swiss(sys) => false,
+                PairingSystem::Swiss(_)
+                | PairingSystem::RoundRobin(_)
+                | PairingSystem::Elimination(_) => false,
             };
         }
         if should_pair {
-            let standings = self.get_standings();
+            let standings = self.get_phase_standings();
             if let Some(pairings) =
                 self.pairing_sys
                     .pair(&self.player_reg, &self.round_reg, standings)
             {
                 let mut rounds = Vec::with_capacity(pairings.paired.len());
                 for p in pairings.paired {
-                    let r_id = self.round_reg.create_round();
+                    let r_id = self.round_reg.create_round(ctx);
                     for plyr in p {
                         let _ = self.round_reg.add_player_to_round(&r_id, plyr);
                     }
                     rounds.push(r_id);
                 }
+                self.add_pending_pairings(&rounds);
                 return Ok(OpData::Pair(rounds));
             }
         }
@@ -612,11 +1252,12 @@ impl Tournament {
         match &mut self.pairing_sys {
             PairingSystem::Swiss(sys) => sys.unready_player(plyr),
             PairingSystem::Fluid(sys) => sys.unready_player(plyr),
+            PairingSystem::RoundRobin(_) | PairingSystem::Elimination(_) => {}
         };
         Ok(OpData::Nothing)
     }
 
-    pub(crate) fn give_bye(&mut self, ident: &PlayerIdentifier) -> OpResult {
+    pub(crate) fn give_bye(&mut self, ident: &PlayerIdentifier, ctx: &mut OpContext) -> OpResult {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
@@ -624,16 +1265,21 @@ impl Tournament {
             .player_reg
             .get_player_id(ident)
             .ok_or(TournamentError::PlayerLookup)?;
-        let r_id = self.round_reg.create_round();
+        let r_id = self.round_reg.create_round(ctx);
         let _ = self.round_reg.add_player_to_round(&r_id, id);
         // Saftey check: This should never return an Err as we just created the round and gave it a
         // single player
         let round = self.round_reg.get_mut_round(&r_id).unwrap();
         round.record_bye()?;
+        self.rescore_round(&r_id);
         Ok(OpData::GiveBye(r_id))
     }
 
-    pub(crate) fn create_round(&mut self, idents: Vec<PlayerIdentifier>) -> OpResult {
+    pub(crate) fn create_round(
+        &mut self,
+        idents: Vec<PlayerIdentifier>,
+        ctx: &mut OpContext,
+    ) -> OpResult {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
@@ -645,7 +1291,7 @@ impl Tournament {
                 .into_iter()
                 .map(|p| self.player_reg.get_player_id(&p).unwrap())
                 .collect();
-            let r_id = self.round_reg.create_round();
+            let r_id = self.round_reg.create_round(ctx);
             for id in ids {
                 let _ = self.round_reg.add_player_to_round(&r_id, id);
             }
@@ -655,19 +1301,67 @@ impl Tournament {
         }
     }
 
+    pub(crate) fn add_phase(&mut self, preset: TournamentPreset, len: Duration) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.phases.push(TournamentPhase {
+            pairing_sys: pairing_system_factory(&preset, self.game_size),
+            scoring_sys: scoring_system_factory(&preset),
+            round_length: len,
+        });
+        Ok(OpData::Nothing)
+    }
+
+    /// Ends the current phase and starts the next one. The final standings of the current phase
+    /// are used to seed the next phase.
+    pub(crate) fn next_phase(&mut self) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if self.round_reg.active_round_count() != 0 {
+            return Err(TournamentError::ActiveMatches);
+        }
+        if self.phases.is_empty() {
+            return Err(TournamentError::NoNextPhase);
+        }
+        let seeding = self
+            .get_phase_standings()
+            .scores
+            .into_iter()
+            .rev()
+            .map(|(p, _)| p)
+            .collect();
+        let next = self.phases.remove(0);
+        let length = self.round_reg.length;
+        self.round_reg.set_round_length(next.round_length);
+        self.completed_phases.push(TournamentPhase {
+            pairing_sys: std::mem::replace(&mut self.pairing_sys, next.pairing_sys),
+            scoring_sys: std::mem::replace(&mut self.scoring_sys, next.scoring_sys),
+            round_length: length,
+        });
+        self.seeding = seeding;
+        self.phase_start = self.round_reg.rounds.len() as u64;
+        Ok(OpData::Nothing)
+    }
+
+    /// Drops every player that isn't in the top of the standings. Players can't be dropped
+    /// mid-match, so this can only be done between rounds.
     pub(crate) fn cut_to_top(&mut self, len: usize) -> OpResult {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
+        if self.round_reg.active_round_count() != 0 {
+            return Err(TournamentError::ActiveMatches);
+        }
         let player_iter = self
             .get_standings()
             .scores
             .into_iter()
-            .skip(len)
+            .filter(|(_, score)| score.rank() > len)
             .map(|(id, _)| PlayerIdentifier::Id(id));
         for id in player_iter {
-            // This result doesn't matter
-            let _ = self.drop_player(&id);
+            self.drop_player(&id)?;
         }
         Ok(OpData::Nothing)
     }
@@ -687,6 +1381,7 @@ impl PairingSystem {
         match self {
             Self::Swiss(sys) => sys.ready_player(id),
             Self::Fluid(sys) => sys.ready_player(id),
+            Self::RoundRobin(_) | Self::Elimination(_) => {}
         }
     }
 
@@ -694,6 +1389,8 @@ impl PairingSystem {
         match self {
             Self::Swiss(sys) => sys.ready_to_pair(plyr_reg, rnd_reg),
             Self::Fluid(sys) => sys.ready_to_pair(),
+            Self::RoundRobin(sys) => sys.ready_to_pair(rnd_reg),
+            Self::Elimination(sys) => sys.ready_to_pair(plyr_reg, rnd_reg),
         }
     }
 
@@ -709,26 +1406,139 @@ impl PairingSystem {
         match self {
             Self::Swiss(sys) => sys.pair(plyr_reg, rnd_reg, standings),
             Self::Fluid(sys) => sys.pair(plyr_reg, rnd_reg),
+            Self::RoundRobin(sys) => sys.pair(plyr_reg, rnd_reg, standings),
+            Self::Elimination(sys) => sys.pair(plyr_reg, rnd_reg, standings),
         }
     }
 }
 
 impl ScoringSystem {
+    pub fn from_preset(preset: ScoringPreset) -> Self {
+        match preset {
+            ScoringPreset::Standard => ScoringSystem::Standard(StandardScoring::new()),
+            ScoringPreset::Buchholz => ScoringSystem::Buchholz(BuchholzScoring::new()),
+            ScoringPreset::SonnebornBerger => {
+                ScoringSystem::SonnebornBerger(SonnebornBergerScoring::new())
+            }
+            ScoringPreset::Cumulative => ScoringSystem::Cumulative(CumulativeScoring::new()),
+            ScoringPreset::Placement => ScoringSystem::Placement(PlacementScoring::new()),
+        }
+    }
+
+    /// Updates any running totals that the scoring system keeps after a round changes
+    pub fn update_round(&mut self, round_reg: &RoundRegistry, round: &Round, first_match: u64) {
+        match self {
+            ScoringSystem::Standard(s) => s.update_round(round_reg, round, first_match),
+            ScoringSystem::Buchholz(s) => s.update_round(round_reg, round, first_match),
+            ScoringSystem::SonnebornBerger(s) => s.update_round(round_reg, round, first_match),
+            ScoringSystem::Cumulative(s) => s.update_round(round_reg, round, first_match),
+            ScoringSystem::Placement(s) => s.update_round(round_reg, round, first_match),
+        }
+    }
+
+    /// Checks any running totals that the scoring system keeps against a full recount
+    pub fn verify_cache(&self, round_reg: &RoundRegistry, first_match: u64) -> bool {
+        match self {
+            ScoringSystem::Standard(s) => s.verify_cache(round_reg, first_match),
+            ScoringSystem::Buchholz(s) => s.verify_cache(round_reg, first_match),
+            ScoringSystem::SonnebornBerger(s) => s.verify_cache(round_reg, first_match),
+            ScoringSystem::Cumulative(s) => s.verify_cache(round_reg, first_match),
+            ScoringSystem::Placement(s) => s.verify_cache(round_reg, first_match),
+        }
+    }
+
+    /// Calculates the standings from the rounds with a match number of at least `first_match`,
+    /// i.e. the rounds of the current phase
     pub fn get_standings(
         &self,
         player_reg: &PlayerRegistry,
         round_reg: &RoundRegistry,
-    ) -> Standings<StandardScore> {
+        first_match: u64,
+    ) -> Standings<TournamentScore> {
+        let scores = match self {
+            ScoringSystem::Standard(s) => TournamentScore::wrap(
+                s.get_standings(player_reg, round_reg, first_match),
+                TournamentScore::Standard,
+            ),
+            ScoringSystem::Buchholz(s) => TournamentScore::wrap(
+                s.get_standings(player_reg, round_reg, first_match),
+                TournamentScore::Chess,
+            ),
+            ScoringSystem::SonnebornBerger(s) => TournamentScore::wrap(
+                s.get_standings(player_reg, round_reg, first_match),
+                TournamentScore::Chess,
+            ),
+            ScoringSystem::Cumulative(s) => TournamentScore::wrap(
+                s.get_standings(player_reg, round_reg, first_match),
+                TournamentScore::Chess,
+            ),
+            ScoringSystem::Placement(s) => TournamentScore::wrap(
+                s.get_standings(player_reg, round_reg, first_match),
+                TournamentScore::Placement,
+            ),
+        };
+        Standings::new(scores)
+    }
+}
+
+impl TournamentScore {
+    fn wrap<S>(standings: Standings<S>, f: fn(S) -> Self) -> Vec<(PlayerId, Self)> {
+        standings
+            .scores
+            .into_iter()
+            .map(|(id, s)| (id, f(s)))
+            .collect()
+    }
+
+    /// Gets the player's place in the standings, starting from 1
+    pub fn rank(&self) -> usize {
+        match self {
+            TournamentScore::Standard(s) => s.rank,
+            TournamentScore::Chess(s) => s.rank,
+            TournamentScore::Placement(s) => s.rank,
+        }
+    }
+}
+
+impl Score for TournamentScore {
+    fn primary_score(&self) -> f64 {
         match self {
-            ScoringSystem::Standard(s) => s.get_standings(player_reg, round_reg),
+            TournamentScore::Standard(s) => s.primary_score(),
+            TournamentScore::Chess(s) => s.primary_score(),
+            TournamentScore::Placement(s) => s.primary_score(),
         }
     }
 }
 
+impl fmt::Display for TournamentScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TournamentScore::Standard(s) => write!(f, "{s}"),
+            TournamentScore::Chess(s) => write!(f, "{s}"),
+            TournamentScore::Placement(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+fn default_team_size() -> u8 {
+    1
+}
+
+fn default_extra_turns() -> u8 {
+    5
+}
+
 pub fn pairing_system_factory(preset: &TournamentPreset, game_size: u8) -> PairingSystem {
     match preset {
         TournamentPreset::Swiss => PairingSystem::Swiss(SwissPairings::new(game_size)),
         TournamentPreset::Fluid => PairingSystem::Fluid(FluidPairings::new(game_size)),
+        TournamentPreset::RoundRobin => PairingSystem::RoundRobin(RoundRobinPairings::new()),
+        TournamentPreset::SingleElimination => {
+            PairingSystem::Elimination(EliminationPairings::new(false))
+        }
+        TournamentPreset::DoubleElimination => {
+            PairingSystem::Elimination(EliminationPairings::new(true))
+        }
     }
 }
 
@@ -736,5 +1546,439 @@ pub fn scoring_system_factory(preset: &TournamentPreset) -> ScoringSystem {
     match preset {
         TournamentPreset::Swiss => ScoringSystem::Standard(StandardScoring::new()),
         TournamentPreset::Fluid => ScoringSystem::Standard(StandardScoring::new()),
+        TournamentPreset::RoundRobin => ScoringSystem::Standard(StandardScoring::new()),
+        TournamentPreset::SingleElimination => ScoringSystem::Standard(StandardScoring::new()),
+        TournamentPreset::DoubleElimination => ScoringSystem::Standard(StandardScoring::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn apply(tourn: &mut Tournament, op: TournOp) -> OpResult {
+        tourn.apply_op(op, OpContext::new(Uuid::new_v4(), SystemTime::now()))
+    }
+
+    #[test]
+    fn dead_rounds_dont_block_the_next_phase() {
+        let mut tourn =
+            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Pioneer".into());
+        for name in ["Alice", "Bob", "Carol", "Dave"] {
+            apply(&mut tourn, TournOp::RegisterPlayer(name.into())).unwrap();
+        }
+        apply(&mut tourn, TournOp::Start()).unwrap();
+        apply(
+            &mut tourn,
+            TournOp::AddPhase(
+                TournamentPreset::SingleElimination,
+                Duration::from_secs(3000),
+            ),
+        )
+        .unwrap();
+        let rounds = match apply(&mut tourn, TournOp::PairRound()).unwrap() {
+            OpData::Pair(rounds) => rounds,
+            _ => panic!("The round should have been paired"),
+        };
+        assert!(matches!(
+            apply(&mut tourn, TournOp::NextPhase()),
+            Err(TournamentError::ActiveMatches)
+        ));
+        for rnd in rounds {
+            apply(&mut tourn, TournOp::RemoveRound(rnd)).unwrap();
+        }
+        apply(&mut tourn, TournOp::NextPhase()).unwrap();
+        assert!(tourn.phases.is_empty());
+    }
+
+    #[test]
+    fn drawn_elimination_matches_are_replayed() {
+        let mut tourn = Tournament::from_preset(
+            "Test".into(),
+            TournamentPreset::SingleElimination,
+            "Pioneer".into(),
+        );
+        let mut plyrs = Vec::new();
+        for name in ["Alice", "Bob"] {
+            match apply(&mut tourn, TournOp::RegisterPlayer(name.into())).unwrap() {
+                OpData::RegisterPlayer(ident) => plyrs.push(tourn.get_player(&ident).unwrap().id),
+                _ => panic!("The player should have been registered"),
+            }
+        }
+        apply(&mut tourn, TournOp::Start()).unwrap();
+        let pair = |tourn: &mut Tournament| match apply(tourn, TournOp::PairRound()).unwrap() {
+            OpData::Pair(rounds) => rounds,
+            _ => panic!("The round should have been paired"),
+        };
+        let rounds = pair(&mut tourn);
+        assert_eq!(rounds.len(), 1);
+        apply(
+            &mut tourn,
+            TournOp::CertifyRound(rounds[0].clone(), vec![RoundResult::Draw()]),
+        )
+        .unwrap();
+        let replay = pair(&mut tourn);
+        assert_eq!(replay.len(), 1);
+        assert_ne!(replay, rounds);
+        let rnd = tourn.round_reg.get_round(&replay[0]).unwrap();
+        assert_eq!(rnd.get_all_players(), plyrs.iter().cloned().collect());
+        apply(
+            &mut tourn,
+            TournOp::CertifyRound(
+                replay[0].clone(),
+                vec![RoundResult::Wins(plyrs[1].clone(), 2)],
+            ),
+        )
+        .unwrap();
+        let bracket = match &tourn.pairing_sys {
+            PairingSystem::Elimination(sys) => sys.bracket().unwrap(),
+            _ => panic!("The tournament should use elimination pairings"),
+        };
+        assert_eq!(bracket.champion(), Some(&plyrs[1]));
+    }
+
+    #[test]
+    fn team_size_is_fixed_once_anyone_registers() {
+        let mut tourn =
+            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Pioneer".into());
+        let team_size = |n| TournOp::UpdateTournSetting(TournamentSetting::TeamSize(n));
+        assert!(matches!(
+            apply(&mut tourn, team_size(0)),
+            Err(TournamentError::IncorrectTeamSize)
+        ));
+        apply(&mut tourn, team_size(2)).unwrap();
+        apply(&mut tourn, team_size(1)).unwrap();
+        apply(&mut tourn, TournOp::RegisterPlayer("Alice".into())).unwrap();
+        assert!(matches!(
+            apply(&mut tourn, team_size(2)),
+            Err(TournamentError::IncorrectTeamSize)
+        ));
+        assert_eq!(tourn.team_size, 1);
+
+        let mut tourn =
+            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Pioneer".into());
+        apply(&mut tourn, TournOp::Start()).unwrap();
+        assert!(matches!(
+            apply(&mut tourn, team_size(2)),
+            Err(TournamentError::IncorrectStatus(TournamentStatus::Started))
+        ));
+    }
+
+    #[test]
+    fn accounts_register_themselves_as_players() {
+        let mut tourn =
+            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Pioneer".into());
+        let account = |name: &str| UserAccount {
+            external_id: AccountId(Uuid::new_v4()),
+            display_name: name.into(),
+            account_name: name.into(),
+        };
+        let (alice, bob) = (account("Alice"), account("Bob"));
+        let author = tourn.author_for(&alice.external_id);
+        assert_eq!(author, OpAuthor::Account(alice.external_id.clone()));
+        // Accounts with no role can only register themselves
+        let own = TournOp::RegisterPlayerAccount(alice.clone());
+        assert!(tourn.check_authority(&author, &own).is_ok());
+        for op in [
+            TournOp::RegisterPlayerAccount(bob.clone()),
+            TournOp::RegisterPlayer("Alice".into()),
+        ] {
+            assert!(tourn.check_authority(&author, &op).is_err());
+        }
+        let id = match apply(&mut tourn, own.clone()).unwrap() {
+            OpData::RegisterPlayer(PlayerIdentifier::Id(id)) => id,
+            _ => panic!("The player should have been registered"),
+        };
+        assert!(apply(&mut tourn, own).is_err());
+
+        // Once registered, the account acts as that player
+        let author = tourn.author_for(&alice.external_id);
+        assert_eq!(author, OpAuthor::Player(id.clone()));
+        let tag = TournOp::SetGamerTag(PlayerIdentifier::Id(id), "Al".into());
+        assert!(tourn.check_authority(&author, &tag).is_ok());
+        apply(&mut tourn, TournOp::RegisterPlayerAccount(bob.clone())).unwrap();
+        let other = tourn
+            .player_reg
+            .get_account_player(&bob.external_id)
+            .unwrap();
+        let tag = TournOp::SetGamerTag(PlayerIdentifier::Id(other), "B".into());
+        assert!(tourn.check_authority(&author, &tag).is_err());
+    }
+
+    /// Creates a started Swiss tournament with the given players and pairs its first round
+    fn paired_swiss(names: &[&str]) -> (Tournament, Vec<RoundIdentifier>) {
+        let mut tourn =
+            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Pioneer".into());
+        for name in names {
+            apply(&mut tourn, TournOp::RegisterPlayer((*name).into())).unwrap();
+        }
+        apply(&mut tourn, TournOp::Start()).unwrap();
+        let rounds = match apply(&mut tourn, TournOp::PairRound()).unwrap() {
+            OpData::Pair(rounds) => rounds,
+            _ => panic!("The round should have been paired"),
+        };
+        (tourn, rounds)
+    }
+
+    /// Certifies each round as a win for its first player
+    fn certify_wins(tourn: &mut Tournament, rounds: &[RoundIdentifier]) {
+        for rnd in rounds {
+            let winner = tourn.round_reg.get_round(rnd).unwrap().players[0].clone();
+            apply(
+                tourn,
+                TournOp::CertifyRound(rnd.clone(), vec![RoundResult::Wins(winner, 2)]),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn phases_are_scored_on_their_own() {
+        let (mut tourn, rounds) = paired_swiss(&["Alice", "Bob", "Carol", "Dave"]);
+        apply(
+            &mut tourn,
+            TournOp::AddPhase(TournamentPreset::Swiss, Duration::from_secs(3000)),
+        )
+        .unwrap();
+        certify_wins(&mut tourn, &rounds);
+        assert!(tourn
+            .get_standings()
+            .scores
+            .iter()
+            .any(|(_, s)| s.primary_score() > 0.0));
+        apply(&mut tourn, TournOp::NextPhase()).unwrap();
+        assert!(tourn
+            .get_standings()
+            .scores
+            .iter()
+            .all(|(_, s)| s.primary_score() == 0.0));
+
+        let rounds = match apply(&mut tourn, TournOp::PairRound()).unwrap() {
+            OpData::Pair(rounds) => rounds,
+            _ => panic!("The round should have been paired"),
+        };
+        certify_wins(&mut tourn, &rounds);
+        // Only the wins from this phase are counted
+        let standings = tourn.get_standings();
+        for rnd in &rounds {
+            let rnd = tourn.round_reg.get_round(rnd).unwrap();
+            let points = |p: &PlayerId| {
+                standings
+                    .scores
+                    .iter()
+                    .find(|(id, _)| id == p)
+                    .unwrap()
+                    .1
+                    .primary_score()
+            };
+            assert_eq!(points(&rnd.players[0]), 3.0);
+            assert_eq!(points(&rnd.players[1]), 0.0);
+        }
+        assert!(tourn.verify_standings());
+    }
+
+    #[test]
+    fn cuts_wait_for_active_rounds() {
+        let (mut tourn, rounds) = paired_swiss(&["Alice", "Bob", "Carol", "Dave"]);
+        assert!(matches!(
+            apply(&mut tourn, TournOp::Cut(2)),
+            Err(TournamentError::ActiveMatches)
+        ));
+        assert_eq!(tourn.player_reg.active_player_count(), 4);
+        certify_wins(&mut tourn, &rounds);
+        apply(&mut tourn, TournOp::Cut(2)).unwrap();
+        assert_eq!(tourn.player_reg.active_player_count(), 2);
+        for rnd in &rounds {
+            let winner = &tourn.round_reg.get_round(rnd).unwrap().players[0];
+            assert!(tourn.player_reg.players[winner].can_play());
+        }
+    }
+
+    #[test]
+    fn teams_are_paired_and_scored_as_one_player() {
+        let mut tourn =
+            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Pioneer".into());
+        apply(
+            &mut tourn,
+            TournOp::UpdateTournSetting(TournamentSetting::TeamSize(2)),
+        )
+        .unwrap();
+        assert!(matches!(
+            apply(&mut tourn, TournOp::RegisterPlayer("Alice".into())),
+            Err(TournamentError::IncorrectTeamSize)
+        ));
+        assert!(matches!(
+            apply(
+                &mut tourn,
+                TournOp::RegisterTeam("Solo".into(), vec!["Alice".into()])
+            ),
+            Err(TournamentError::IncorrectTeamSize)
+        ));
+        for team in ["Red", "Blue"] {
+            let members = vec![format!("{team} A"), format!("{team} B")];
+            apply(&mut tourn, TournOp::RegisterTeam(team.into(), members)).unwrap();
+        }
+        apply(&mut tourn, TournOp::Start()).unwrap();
+        let rounds = match apply(&mut tourn, TournOp::PairRound()).unwrap() {
+            OpData::Pair(rounds) => rounds,
+            _ => panic!("The round should have been paired"),
+        };
+        assert_eq!(rounds.len(), 1);
+        let rnd = tourn.get_round(&rounds[0]).unwrap();
+        assert_eq!(rnd.players.len(), 2);
+        assert!(rnd.players.iter().all(|p| tourn.player_reg.is_team(p)));
+
+        // Members in the same seat play each other
+        let teams: Vec<Team> = rnd
+            .players
+            .iter()
+            .map(|p| tourn.get_team(&PlayerIdentifier::Id(p.clone())).unwrap())
+            .collect();
+        let matchups = tourn.get_round_matchups(&rounds[0]).unwrap();
+        assert_eq!(
+            matchups,
+            (0..2)
+                .map(|seat| teams.iter().map(|t| t.members[seat].id.clone()).collect())
+                .collect::<Vec<Vec<PlayerId>>>()
+        );
+
+        certify_wins(&mut tourn, &rounds);
+        let standings = tourn.get_standings();
+        assert_eq!(standings.scores.len(), 2);
+        // Standings go from last place to first
+        assert_eq!(standings.scores[1].0, rnd.players[0]);
+        assert_eq!(standings.scores[1].1.primary_score(), 3.0);
+        assert_eq!(standings.scores[0].1.primary_score(), 0.0);
+    }
+
+    /// Checks that the cached standings match a full recount. The cache isn't serialized, so a
+    /// copy of the scoring system made through serde tallies every round from scratch.
+    fn assert_cache_matches_recount(tourn: &Tournament) {
+        assert!(tourn.verify_standings());
+        let recount: ScoringSystem =
+            serde_json::from_str(&serde_json::to_string(&tourn.scoring_sys).unwrap()).unwrap();
+        assert_eq!(
+            tourn.get_standings().scores,
+            recount
+                .get_standings(&tourn.player_reg, &tourn.round_reg, tourn.phase_start)
+                .scores
+        );
+    }
+
+    #[test]
+    fn cached_standings_match_a_full_recount() {
+        for preset in [
+            ScoringPreset::Standard,
+            ScoringPreset::Buchholz,
+            ScoringPreset::SonnebornBerger,
+            ScoringPreset::Cumulative,
+            ScoringPreset::Placement,
+        ] {
+            let (mut tourn, rounds) =
+                paired_swiss(&["Alice", "Bob", "Carol", "Dave", "Eve", "Frank"]);
+            apply(
+                &mut tourn,
+                TournOp::UpdateTournSetting(TournamentSetting::ScoringPreset(preset)),
+            )
+            .unwrap();
+            // Recording results
+            certify_wins(&mut tourn, &rounds);
+            assert_cache_matches_recount(&tourn);
+            // Correcting a result
+            let loser = tourn.round_reg.get_round(&rounds[0]).unwrap().players[1].clone();
+            apply(
+                &mut tourn,
+                TournOp::CertifyRound(rounds[0].clone(), vec![RoundResult::Wins(loser, 2)]),
+            )
+            .unwrap();
+            assert_cache_matches_recount(&tourn);
+            // Killing a round
+            apply(&mut tourn, TournOp::RemoveRound(rounds[1].clone())).unwrap();
+            assert_cache_matches_recount(&tourn);
+        }
+    }
+
+    fn pair(tourn: &mut Tournament) -> Vec<RoundIdentifier> {
+        match apply(tourn, TournOp::PairRound()).unwrap() {
+            OpData::Pair(rounds) => rounds,
+            _ => panic!("The round should have been paired"),
+        }
+    }
+
+    #[test]
+    fn standings_are_recorded_once_a_round_of_pairings_is_done() {
+        let (mut tourn, rounds) = paired_swiss(&["Alice", "Bob", "Carol", "Dave"]);
+        let round = tourn.round_reg.get_round(&rounds[0]).unwrap();
+        let (winner, loser) = (round.players[0].clone(), round.players[1].clone());
+        certify_wins(&mut tourn, &rounds[..1]);
+        assert!(tourn.standings_history.is_empty());
+        certify_wins(&mut tourn, &rounds[1..]);
+        assert_eq!(tourn.standings_history.len(), 1);
+        let history = tourn
+            .get_rank_history(&PlayerIdentifier::Id(loser))
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].round, 1);
+        assert!(history[0].rank > 2);
+        // A killed round doesn't hold up the rest of its pairings
+        let rounds = pair(&mut tourn);
+        apply(&mut tourn, TournOp::RemoveRound(rounds[0].clone())).unwrap();
+        assert_eq!(tourn.standings_history.len(), 1);
+        certify_wins(&mut tourn, &rounds[1..]);
+        assert_eq!(tourn.standings_history.len(), 2);
+        let history = tourn
+            .get_rank_history(&PlayerIdentifier::Id(winner.clone()))
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].rank <= 2);
+        for (i, entry) in history.iter().enumerate() {
+            assert_eq!(entry.round, i + 1);
+            let snapshot = &tourn.standings_history[i].standings.scores;
+            let (_, score) = snapshot.iter().find(|(p, _)| p == &winner).unwrap();
+            assert_eq!(&entry.score, score);
+            assert_eq!(entry.rank, score.rank());
+        }
+    }
+
+    #[test]
+    fn rank_history_needs_a_known_player() {
+        let (tourn, _) = paired_swiss(&["Alice", "Bob"]);
+        assert!(matches!(
+            tourn.get_rank_history(&PlayerIdentifier::Name("Carol".into())),
+            Err(TournamentError::PlayerLookup)
+        ));
+        assert!(tourn
+            .get_rank_history(&PlayerIdentifier::Name("Alice".into()))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn certified_results_can_be_corrected() {
+        let (mut tourn, rounds) = paired_swiss(&["Alice", "Bob"]);
+        let players = tourn
+            .round_reg
+            .get_round(&rounds[0])
+            .unwrap()
+            .players
+            .clone();
+        let (one, two) = (players[0].clone(), players[1].clone());
+        certify_wins(&mut tourn, &rounds);
+        // Standings go from last place to first
+        let leader = |tourn: &Tournament| tourn.get_standings().scores.last().unwrap().0.clone();
+        assert_eq!(leader(&tourn), one);
+        // A 2-0 win is corrected to a 2-0 loss
+        for result in [RoundResult::Wins(one, 0), RoundResult::Wins(two.clone(), 2)] {
+            apply(&mut tourn, TournOp::RecordResult(rounds[0].clone(), result)).unwrap();
+        }
+        let rnd = tourn.round_reg.get_round(&rounds[0]).unwrap();
+        assert!(rnd.is_certified());
+        assert_eq!(rnd.winner, Some(two.clone()));
+        assert_eq!(leader(&tourn), two);
+        assert!(tourn.verify_standings());
     }
 }
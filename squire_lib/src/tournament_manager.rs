@@ -1,14 +1,22 @@
 use crate::{
-    error::TournamentError,
-    operations::{FullOp, OpLog, OpResult, OpSlice, OpSync, Rollback, SyncStatus, Synced, TournOp},
+    error::{SyncError, TournamentError},
+    operations::{
+        AuditFilter, FullOp, OpAuthor, OpId, OpLog, OpResult, OpSlice, OpSync, Rollback,
+        SyncStatus, Synced, TournOp,
+    },
+    player::PlayerId,
     player_registry::PlayerIdentifier,
+    round::RoundId,
     round_registry::RoundIdentifier,
     tournament::*,
 };
 
 use serde::{Deserialize, Serialize};
 
-use std::slice::Iter;
+use std::{collections::BTreeMap, slice::Iter};
+
+/// The number of operations between each snapshot of the tournament that the manager keeps
+const SNAPSHOT_INTERVAL: usize = 32;
 
 /// A state manager for the tournament struct
 ///
@@ -22,9 +30,42 @@ pub struct TournamentManager {
     name: String,
     format: String,
     log: OpLog,
+    /// Copies of the tournament keyed by the number of operations applied to them. These are used
+    /// to avoid replaying the whole log when recreating a prior state. Each one is a full copy, so
+    /// they are only taken every `SNAPSHOT_INTERVAL` operations.
+    #[serde(skip)]
+    snapshots: BTreeMap<usize, Tournament>,
+    /// The active operations in the log that returned an error the last time they were applied.
+    /// These ops are stored but have no effect on the tournament.
+    #[serde(default)]
+    failed_ops: Vec<(OpId, TournamentError)>,
+}
+
+/// The differences between two states of a tournament
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TournamentDiff {
+    pub status: Option<(TournamentStatus, TournamentStatus)>,
+    pub reg_open: Option<(bool, bool)>,
+    pub added_players: Vec<PlayerId>,
+    pub changed_players: Vec<PlayerId>,
+    pub added_rounds: Vec<RoundId>,
+    pub changed_rounds: Vec<RoundId>,
 }
 
 impl TournamentManager {
+    /// Creates a manager, and the tournament it manages, from a name, preset, and format
+    pub fn from_preset(name: String, preset: TournamentPreset, format: String) -> Self {
+        TournamentManager {
+            tourn: Tournament::from_preset(name.clone(), preset, format.clone()),
+            seed: preset,
+            name,
+            format,
+            log: OpLog::new(),
+            snapshots: BTreeMap::new(),
+            failed_ops: Vec::new(),
+        }
+    }
+
     /// Read only accesses to tournaments don't need to be wrapped, so we can freely provide
     /// references to them
     pub fn get_state(&self) -> &Tournament {
@@ -37,10 +78,86 @@ impl TournamentManager {
         self.tourn
     }
 
+    /// Returns the active operations in the log that failed when they were last applied, along
+    /// with their errors. An operation that succeeded when it was submitted can fail once the log
+    /// is rebuilt, e.g. after a sync or rollback reorders operations.
+    pub fn failed_ops(&self) -> &[(OpId, TournamentError)] {
+        &self.failed_ops
+    }
+
+    /// Returns a slice containing every operation in the log
+    pub fn get_op_slice(&self) -> OpSlice {
+        OpSlice {
+            ops: self.log.ops.clone(),
+        }
+    }
+
+    /// Checks the operations that a remote author sent to start a sync. See `check_remote_ops`.
+    pub fn check_sync(&self, author: &OpAuthor, sync: &OpSync) -> Result<(), TournamentError> {
+        self.check_remote_ops(author, &sync.ops)
+    }
+
+    /// Checks the operations that a remote author sent to finish a sync. See `check_remote_ops`.
+    pub fn check_import(&self, author: &OpAuthor, sync: &Synced) -> Result<(), TournamentError> {
+        self.check_remote_ops(author, &sync.known)
+    }
+
+    /// Operations that are already in the log must be unchanged. Every new operation must be
+    /// recorded under the given author, who must be allowed to submit it.
+    fn check_remote_ops(&self, author: &OpAuthor, ops: &OpSlice) -> Result<(), TournamentError> {
+        for op in &ops.ops {
+            match self.log.ops.iter().find(|o| o.id == op.id) {
+                Some(known) if known == op => {}
+                Some(_) => return Err(TournamentError::InsufficientPermissions),
+                None => {
+                    if op.author != *author {
+                        return Err(TournamentError::InsufficientPermissions);
+                    }
+                    self.tourn.check_authority(author, &op.op)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a rollback sent by a remote author. Only admins can roll back operations, and a
+    /// rollback can only change which of the logged operations are active.
+    pub fn check_rollback(
+        &self,
+        author: &OpAuthor,
+        rollback: &Rollback,
+    ) -> Result<(), TournamentError> {
+        let is_admin = match author {
+            OpAuthor::Admin(id) => self.tourn.admins.contains_key(id),
+            _ => false,
+        };
+        if !is_admin {
+            return Err(TournamentError::InsufficientPermissions);
+        }
+        let unchanged = rollback.ops.ops.iter().all(|op| {
+            self.log.ops.iter().any(|o| {
+                o.id == op.id
+                    && o.op == op.op
+                    && o.author == op.author
+                    && o.time == op.time
+                    && o.reason == op.reason
+            })
+        });
+        if unchanged {
+            Ok(())
+        } else {
+            Err(TournamentError::InsufficientPermissions)
+        }
+    }
+
     /// Starts the syncing processing. If syncing can occur, the op logs are merged and
     /// SyncStatus::Completed is returned.
     pub fn start_sync(&mut self, sy: OpSync) -> SyncStatus {
-        todo!()
+        let digest = self.log.sync(sy);
+        if let SyncStatus::Completed(_) = &digest {
+            self.rebuild();
+        }
+        digest
     }
 
     /// Imports a synced op log. Returns Ok containing the given Synced if this log hasn't changed.
@@ -48,24 +165,201 @@ impl TournamentManager {
     /// Completed, the this log is updated accordingly (otherwise, the method would have to be
     /// immediately called again).
     pub fn import_sync(&mut self, ops: Synced) -> Result<Synced, SyncStatus> {
-        todo!()
+        let id = match ops.known.start_id() {
+            Some(id) => id,
+            None => {
+                return Ok(ops);
+            }
+        };
+        let slice = match self.log.get_slice(id) {
+            Some(s) => s,
+            None => {
+                return Err(SyncStatus::SyncError(OpSync { ops: ops.known }));
+            }
+        };
+        // If every local operation is already known, nothing has changed since the sync started
+        if slice
+            .ops
+            .iter()
+            .all(|o| ops.known.ops.iter().any(|k| k.id == o.id))
+        {
+            // Saftey check: We just found the starting id in the log
+            let _ = self.log.overwrite(ops.known.clone());
+            self.rebuild();
+            return Ok(ops);
+        }
+        match slice.merge(ops.known) {
+            Ok(known) => {
+                let _ = self.log.overwrite(known.clone());
+                self.rebuild();
+                Err(SyncStatus::Completed(Synced { known }))
+            }
+            Err(block) => Err(SyncStatus::InProgress(block)),
+        }
+    }
+
+    /// Removes everything in the log starting at the first operation in the given slice, appends
+    /// the slice, and then rebuilds the tournament. This is used to import a rollback.
+    pub fn overwrite(&mut self, ops: OpSlice) -> Result<(), SyncError> {
+        self.log.overwrite(ops).ok_or(SyncError::IdNotFound)?;
+        self.rebuild();
+        Ok(())
     }
 
-    pub fn overwrite(&mut self, ops: OpSlice) {
-        todo!()
+    /// Imports a rollback. SyncStatus::Completed is returned if the rollback was applied. If the
+    /// first operation of the rollback is unknown, SyncStatus::SyncError is returned.
+    pub fn apply_rollback(&mut self, rollback: Rollback) -> SyncStatus {
+        let ops: OpSlice = rollback.into();
+        match self.overwrite(ops.clone()) {
+            Ok(()) => SyncStatus::Completed(Synced { known: ops }),
+            Err(_) => SyncStatus::SyncError(OpSync { ops }),
+        }
     }
 
-    pub fn propose_rollback<F>(&mut self, f: F) -> Rollback
+    /// Creates a rollback by walking the log from the end. Operations that cause the closure to
+    /// return `true` are deactivated and those that return `false` are kept. The walk ends at the
+    /// first operation that returns `None`.
+    ///
+    /// The manager is not changed. Once the rollback has been accepted, it can be applied via
+    /// `apply_rollback`.
+    pub fn propose_rollback<F>(&self, f: F) -> Rollback
     where
         F: FnMut(&FullOp) -> Option<bool>,
     {
-        todo!()
+        self.log.rollback(f)
+    }
+
+    /// Returns every operation in the log that passes the given filter
+    pub fn audit(&self, filter: &AuditFilter) -> Vec<FullOp> {
+        self.log
+            .ops
+            .iter()
+            .filter(|o| filter.matches(o))
+            .filter(|o| match &filter.player {
+                Some(id) => self.affected_players(&o.op).contains(id),
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns all players affected by an operation. For operations on rounds, this is everyone in
+    /// the round.
+    fn affected_players(&self, op: &TournOp) -> Vec<PlayerId> {
+        if let TournOp::ImportPlayer(plyr) = op {
+            return vec![plyr.id.clone()];
+        }
+        if let TournOp::ImportRound(rnd) = op {
+            return rnd.players.to_vec();
+        }
+        let mut digest: Vec<PlayerId> = match (op.get_player_ident(), op.list_player_ident()) {
+            (Some(ident), _) => vec![ident],
+            (None, Some(idents)) => idents,
+            (None, None) => Vec::new(),
+        }
+        .iter()
+        .filter_map(|ident| self.tourn.player_reg.get_player_id(ident))
+        .collect();
+        if let Some(rnd) = op
+            .get_match_ident()
+            .and_then(|ident| self.tourn.round_reg.get_round(&ident))
+        {
+            digest.extend(rnd.players.iter().cloned());
+        }
+        digest
+    }
+
+    /// Recreates the tournament by replaying every active operation in the log on top of the
+    /// seed. The snapshots and the list of failed operations are recreated along the way.
+    fn rebuild(&mut self) {
+        let mut tourn = self.seed_state();
+        self.snapshots.clear();
+        self.failed_ops.clear();
+        for (i, op) in self.log.ops.iter().enumerate() {
+            if op.active {
+                if let Err(err) = tourn.apply_op(op.op.clone(), op.context()) {
+                    self.failed_ops.push((op.id, err));
+                }
+            }
+            if (i + 1).is_multiple_of(SNAPSHOT_INTERVAL) {
+                self.snapshots.insert(i + 1, tourn.clone());
+            }
+        }
+        self.tourn = tourn;
+    }
+
+    /// Creates the tournament that the log is applied to
+    fn seed_state(&self) -> Tournament {
+        let mut digest = Tournament::from_preset(self.name.clone(), self.seed, self.format.clone());
+        digest.id = self.tourn.id;
+        digest
+    }
+
+    /// Recreates the state of the tournament after the first `index` operations in the log have
+    /// been applied. An index of `0` gives the initial state. `None` is returned if the index is
+    /// greater than the length of the log.
+    pub fn state_at(&self, index: usize) -> Option<Tournament> {
+        if index > self.log.ops.len() {
+            return None;
+        }
+        let (start, mut digest) = match self.snapshots.range(..=index).next_back() {
+            Some((i, tourn)) => (*i, tourn.clone()),
+            None => (0, self.seed_state()),
+        };
+        for op in self.log.ops[start..index].iter().filter(|o| o.active) {
+            let _ = digest.apply_op(op.op.clone(), op.context());
+        }
+        Some(digest)
     }
 
-    /// Takes an operation, ensures all idents are their Id variants, stores the operation, applies
-    /// it to the tournament, and returns the result.
-    /// NOTE: That if an operation can be convert, it is always stored, regardless of the outcome
-    pub fn apply_op(&mut self, op: TournOp) -> OpResult {
+    /// Recreates the state of the tournament immediately after the given operation was applied.
+    /// `None` is returned if the operation isn't in the log.
+    pub fn state_at_op(&self, id: OpId) -> Option<Tournament> {
+        let index = self.log.ops.iter().position(|o| o.id == id)?;
+        self.state_at(index + 1)
+    }
+
+    /// Calculates the differences between the states after the first `from` and `to`
+    /// operations have been applied.
+    pub fn diff_states(&self, from: usize, to: usize) -> Option<TournamentDiff> {
+        Some(TournamentDiff::new(
+            &self.state_at(from)?,
+            &self.state_at(to)?,
+        ))
+    }
+
+    /// Finds the first operation after which the given condition holds, e.g. the operation that
+    /// dropped a player. The condition is assumed to hold for every state after that operation,
+    /// which allows the log to be binary searched.
+    ///
+    /// `None` is returned if the condition already holds for the initial state or doesn't hold
+    /// for the current state.
+    pub fn bisect<F>(&self, mut f: F) -> Option<FullOp>
+    where
+        F: FnMut(&Tournament) -> bool,
+    {
+        let mut low = 0;
+        let mut high = self.log.ops.len();
+        if f(&self.state_at(low)?) || !f(&self.state_at(high)?) {
+            return None;
+        }
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if f(&self.state_at(mid)?) {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        self.log.ops.get(high - 1).cloned()
+    }
+
+    /// Takes an operation, ensures all idents are their Id variants, checks that the author is
+    /// allowed to submit it, stores the operation, applies it to the tournament, and returns the
+    /// result. The author and reason are recorded with the operation.
+    /// NOTE: That if an operation can be convert and the author has the needed permissions, it is
+    /// always stored, regardless of the outcome
+    pub fn apply_op(&mut self, op: TournOp, author: OpAuthor, reason: Option<String>) -> OpResult {
         let op = if let Some(ident) = op.get_player_ident() {
             let id = self
                 .tourn
@@ -94,19 +388,27 @@ impl TournamentManager {
         } else {
             op
         };
-        let f_op = FullOp::new(op.clone());
+        self.tourn.check_authority(&author, &op)?;
+        let f_op = FullOp::new(op.clone(), author, reason);
+        let ctx = f_op.context();
+        let id = f_op.id;
         self.log.ops.push(f_op);
-        self.tourn.apply_op(op)
+        let digest = self.tourn.apply_op(op, ctx);
+        if let Err(err) = &digest {
+            self.failed_ops.push((id, *err));
+        }
+        if self.log.ops.len().is_multiple_of(SNAPSHOT_INTERVAL) {
+            self.snapshots
+                .insert(self.log.ops.len(), self.tourn.clone());
+        }
+        digest
     }
 
-    /// Returns an iterator over all the states of a tournament
+    /// Returns an iterator over all the states of a tournament. Every state is a full copy of the
+    /// tournament, so `state_at` should be used when only a few states are needed.
     pub fn states(&self) -> StateIter {
         StateIter {
-            state: Tournament::from_preset(
-                self.name.clone(),
-                self.seed.clone(),
-                self.format.clone(),
-            ),
+            state: self.seed_state(),
             ops: self.log.ops.iter(),
             shown_init: false,
         }
@@ -126,10 +428,365 @@ impl Iterator for StateIter<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         if self.shown_init {
             let op = self.ops.next()?;
-            let _ = self.state.apply_op(op.op.clone());
+            if op.active {
+                let _ = self.state.apply_op(op.op.clone(), op.context());
+            }
         } else {
             self.shown_init = true;
         }
         Some(self.state.clone())
     }
 }
+
+impl TournamentDiff {
+    /// Calculates the differences between two states of a tournament
+    pub fn new(old: &Tournament, new: &Tournament) -> Self {
+        let mut digest = TournamentDiff {
+            status: None,
+            reg_open: None,
+            added_players: Vec::new(),
+            changed_players: Vec::new(),
+            added_rounds: Vec::new(),
+            changed_rounds: Vec::new(),
+        };
+        if old.status != new.status {
+            digest.status = Some((old.status, new.status));
+        }
+        if old.reg_open != new.reg_open {
+            digest.reg_open = Some((old.reg_open, new.reg_open));
+        }
+        for (id, plyr) in new.player_reg.players.iter() {
+            match old.player_reg.players.get(id) {
+                None => digest.added_players.push(id.clone()),
+                Some(p) => {
+                    if p.status != plyr.status
+                        || p.game_name != plyr.game_name
+                        || p.deck_ordering != plyr.deck_ordering
+                    {
+                        digest.changed_players.push(id.clone());
+                    }
+                }
+            }
+        }
+        for rnd in new.round_reg.rounds.values() {
+            match old
+                .round_reg
+                .get_round(&RoundIdentifier::Id(rnd.id.clone()))
+            {
+                None => digest.added_rounds.push(rnd.id.clone()),
+                Some(r) => {
+                    if r.status != rnd.status
+                        || r.players != rnd.players
+                        || r.results != rnd.results
+                        || r.confirmations != rnd.confirmations
+                        || r.winner != rnd.winner
+                        || r.extension != rnd.extension
+                    {
+                        digest.changed_rounds.push(rnd.id.clone());
+                    }
+                }
+            }
+        }
+        digest
+    }
+
+    /// Returns `true` if there are no differences
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.reg_open.is_none()
+            && self.added_players.is_empty()
+            && self.changed_players.is_empty()
+            && self.added_rounds.is_empty()
+            && self.changed_rounds.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        operations::OpData,
+        round::RoundResult,
+        settings::{ScoringSetting, StandardScoringSetting, TournamentSetting},
+    };
+
+    fn manager() -> TournamentManager {
+        TournamentManager::from_preset(
+            "Test".to_owned(),
+            TournamentPreset::Swiss,
+            "Pioneer".to_owned(),
+        )
+    }
+
+    fn apply(man: &mut TournamentManager, op: TournOp) -> OpResult {
+        man.apply_op(op, OpAuthor::System, None)
+    }
+
+    fn register(man: &mut TournamentManager, name: &str) -> PlayerId {
+        apply(man, TournOp::RegisterPlayer(name.to_owned())).unwrap();
+        man.tourn
+            .player_reg
+            .get_player_id(&PlayerIdentifier::Name(name.to_owned()))
+            .unwrap()
+    }
+
+    fn last_op(man: &TournamentManager) -> OpId {
+        man.log.ops.last().unwrap().id
+    }
+
+    #[test]
+    fn failed_ops_are_logged() {
+        let mut man = manager();
+        register(&mut man, "a");
+        apply(&mut man, TournOp::Start()).unwrap();
+        assert!(apply(&mut man, TournOp::Start()).is_err());
+        let id = last_op(&man);
+        assert_eq!(man.log.ops.len(), 3);
+        assert!(matches!(
+            man.failed_ops(),
+            [(op, TournamentError::IncorrectStatus(_))] if *op == id
+        ));
+        // Rebuilding finds the same failure
+        man.rebuild();
+        assert_eq!(man.failed_ops().len(), 1);
+        assert_eq!(man.failed_ops()[0].0, id);
+    }
+
+    #[test]
+    fn rollback_deactivates_ops() {
+        let mut man = manager();
+        register(&mut man, "a");
+        register(&mut man, "b");
+        let b = last_op(&man);
+        let rollback = man.propose_rollback(|op| (op.id == b).then_some(true));
+        assert!(matches!(
+            man.apply_rollback(rollback),
+            SyncStatus::Completed(_)
+        ));
+        // The op is kept in the log but has no effect
+        assert_eq!(man.log.ops.len(), 2);
+        assert!(!man.log.ops[1].active);
+        assert_eq!(man.tourn.player_reg.players.len(), 1);
+        assert!(man.failed_ops().is_empty());
+    }
+
+    #[test]
+    fn rollback_records_ops_that_now_fail() {
+        let mut man = manager();
+        let a = register(&mut man, "a");
+        let reg = last_op(&man);
+        apply(
+            &mut man,
+            TournOp::SetGamerTag(PlayerIdentifier::Id(a), "tag".to_owned()),
+        )
+        .unwrap();
+        let tag = last_op(&man);
+        // Undo the registration but keep the gamer tag
+        let rollback = man.propose_rollback(|op| Some(op.id == reg));
+        assert!(matches!(
+            man.apply_rollback(rollback),
+            SyncStatus::Completed(_)
+        ));
+        assert!(man.tourn.player_reg.players.is_empty());
+        assert!(matches!(
+            man.failed_ops(),
+            [(op, TournamentError::PlayerLookup)] if *op == tag
+        ));
+    }
+
+    #[test]
+    fn rollbacks_of_unknown_ops_fail() {
+        let mut man = manager();
+        register(&mut man, "a");
+        let mut other = manager();
+        register(&mut other, "a");
+        let rollback = other.propose_rollback(|_| Some(true));
+        assert!(matches!(
+            man.apply_rollback(rollback),
+            SyncStatus::SyncError(_)
+        ));
+        assert_eq!(man.tourn.player_reg.players.len(), 1);
+    }
+
+    /// Tournaments can't be compared directly, so their serialized forms are compared instead
+    fn same_state(one: &Tournament, two: &Tournament) -> bool {
+        serde_json::to_value(one).unwrap() == serde_json::to_value(two).unwrap()
+    }
+
+    #[test]
+    fn state_at_matches_a_full_replay() {
+        let mut man = manager();
+        for i in 0..(2 * SNAPSHOT_INTERVAL + 5) {
+            let id = register(&mut man, &format!("{i}"));
+            apply(
+                &mut man,
+                TournOp::SetGamerTag(PlayerIdentifier::Id(id), format!("tag {i}")),
+            )
+            .unwrap();
+        }
+        assert!(man.snapshots.contains_key(&SNAPSHOT_INTERVAL));
+        let len = man.log.ops.len();
+        // On, between, and on either side of the snapshots
+        for n in [
+            0,
+            1,
+            SNAPSHOT_INTERVAL - 1,
+            SNAPSHOT_INTERVAL,
+            SNAPSHOT_INTERVAL + 1,
+            SNAPSHOT_INTERVAL + 7,
+            2 * SNAPSHOT_INTERVAL,
+            2 * SNAPSHOT_INTERVAL + 3,
+            len,
+        ] {
+            let replayed = man.states().nth(n).unwrap();
+            assert!(same_state(&man.state_at(n).unwrap(), &replayed), "{n}");
+        }
+        assert!(same_state(&man.state_at(len).unwrap(), &man.tourn));
+        assert!(man.state_at(len + 1).is_none());
+    }
+
+    #[test]
+    fn bisect_finds_the_first_change() {
+        let mut man = manager();
+        let a = register(&mut man, "a");
+        for name in ["b", "c"] {
+            register(&mut man, name);
+        }
+        apply(&mut man, TournOp::Start()).unwrap();
+        let start = last_op(&man);
+        apply(
+            &mut man,
+            TournOp::SetGamerTag(PlayerIdentifier::Id(a), "tag".to_owned()),
+        )
+        .unwrap();
+        let found = man
+            .bisect(|t| t.status == TournamentStatus::Started)
+            .unwrap();
+        assert_eq!(found.id, start);
+        let found = man.bisect(|t| t.player_reg.players.len() >= 2).unwrap();
+        assert_eq!(found.id, man.log.ops[1].id);
+        // Conditions that never change can't be bisected
+        assert!(man.bisect(|_| true).is_none());
+        assert!(man.bisect(|_| false).is_none());
+    }
+
+    #[test]
+    fn diffs_between_states() {
+        let mut man = manager();
+        let a = register(&mut man, "a");
+        let b = register(&mut man, "b");
+        apply(&mut man, TournOp::Start()).unwrap();
+        apply(
+            &mut man,
+            TournOp::SetGamerTag(PlayerIdentifier::Id(a.clone()), "tag".to_owned()),
+        )
+        .unwrap();
+        let diff = man.diff_states(1, 3).unwrap();
+        assert_eq!(diff.added_players, vec![b]);
+        assert_eq!(
+            diff.status,
+            Some((TournamentStatus::Planned, TournamentStatus::Started))
+        );
+        assert!(diff.changed_players.is_empty());
+        let diff = man.diff_states(3, 4).unwrap();
+        assert_eq!(diff.changed_players, vec![a]);
+        assert!(diff.status.is_none());
+        assert!(man.diff_states(2, 2).unwrap().is_empty());
+        assert!(man.diff_states(0, 5).is_none());
+    }
+
+    /// Hash sets are serialized in an arbitrary order, so they are sorted before replayed states
+    /// are compared
+    fn replay_state(tourn: &Tournament) -> serde_json::Value {
+        let mut digest = serde_json::to_value(tourn).unwrap();
+        let sort = |set: &mut serde_json::Value| {
+            set.as_array_mut().unwrap().sort_by_key(|v| v.to_string());
+        };
+        let round_reg = &mut digest["round_reg"];
+        for opps in round_reg["opponents"].as_object_mut().unwrap().values_mut() {
+            sort(opps);
+        }
+        for rnd in round_reg["rounds"].as_object_mut().unwrap().values_mut() {
+            sort(&mut rnd["confirmations"]);
+        }
+        digest
+    }
+
+    /// The ids of the players and rounds in the tournament
+    fn ids(tourn: &Tournament) -> (HashSet<PlayerId>, HashSet<RoundId>) {
+        let plyrs = tourn.player_reg.players.keys().cloned().collect();
+        let rnds = tourn
+            .round_reg
+            .rounds
+            .values()
+            .map(|r| r.get_id())
+            .collect();
+        (plyrs, rnds)
+    }
+
+    #[test]
+    fn replaying_the_log_is_deterministic() {
+        let mut man = manager();
+        let plyrs: Vec<PlayerId> = ["a", "b", "c", "d", "e", "f"]
+            .into_iter()
+            .map(|name| register(&mut man, name))
+            .collect();
+        apply(
+            &mut man,
+            TournOp::UpdateTournSetting(TournamentSetting::ScoringSetting(
+                ScoringSetting::Standard(StandardScoringSetting::TiebreakSeed(42)),
+            )),
+        )
+        .unwrap();
+        apply(&mut man, TournOp::Start()).unwrap();
+        for _ in 0..2 {
+            let rounds = match apply(&mut man, TournOp::PairRound()).unwrap() {
+                OpData::Pair(rounds) => rounds,
+                _ => panic!("The round should have been paired"),
+            };
+            let players = man
+                .tourn
+                .round_reg
+                .get_round(&rounds[0])
+                .unwrap()
+                .players
+                .clone();
+            apply(&mut man, TournOp::PauseRound(rounds[0].clone())).unwrap();
+            apply(&mut man, TournOp::ResumeRound(rounds[0].clone())).unwrap();
+            apply(&mut man, TournOp::CallTime(rounds[0].clone())).unwrap();
+            apply(&mut man, TournOp::TakeExtraTurn(rounds[0].clone())).unwrap();
+            apply(
+                &mut man,
+                TournOp::RecordResult(rounds[0].clone(), RoundResult::Wins(players[0].clone(), 2)),
+            )
+            .unwrap();
+            for plyr in players {
+                apply(&mut man, TournOp::ConfirmResult(PlayerIdentifier::Id(plyr))).unwrap();
+            }
+            for rnd in &rounds[1..] {
+                apply(
+                    &mut man,
+                    TournOp::CertifyRound(rnd.clone(), vec![RoundResult::Draw()]),
+                )
+                .unwrap();
+            }
+        }
+        assert_eq!(man.tourn.round_reg.rounds.len(), 6);
+        assert_eq!(man.tourn.standings_history.len(), 2);
+        // Replay the log through both the state iterator and a rebuild
+        let replayed = man.states().last().unwrap();
+        let original = man.tourn.clone();
+        man.rebuild();
+        assert_eq!(replay_state(&original), replay_state(&replayed));
+        assert_eq!(replay_state(&original), replay_state(&man.tourn));
+        assert_eq!(ids(&original), ids(&replayed));
+        assert_eq!(ids(&original), ids(&man.tourn));
+        assert_eq!(ids(&original).0.len(), plyrs.len());
+        assert_eq!(
+            original.get_standings().scores,
+            man.tourn.get_standings().scores
+        );
+    }
+}
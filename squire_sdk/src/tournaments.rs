@@ -4,10 +4,14 @@ use serde::{Deserialize, Serialize};
 use squire_lib::operations::{OpSlice, OpSync, Rollback, SyncStatus, Synced};
 pub use squire_lib::{
     error::TournamentError,
-    operations::{OpResult, TournOp},
+    operations::{AuditFilter, FullOp, OpAuthor, OpResult, TournOp},
+    player_registry::PlayerIdentifier,
     scoring::Standings,
     standard_scoring::StandardScore,
-    tournament::{Tournament, TournamentId, TournamentIdentifier, TournamentPreset},
+    tournament::{
+        RankHistoryEntry, Tournament, TournamentId, TournamentIdentifier, TournamentPreset,
+        TournamentScore,
+    },
 };
 
 use crate::response::SquireResponse;
@@ -35,14 +39,29 @@ pub struct StandingsRequest {
     pub ident: TournamentIdentifier,
 }
 
+/// The standings of a tournament that uses standard scoring. This is `None` if the tournament
+/// uses another scoring system, whose standings can only be gotten as a `StandingsV2Response`.
 pub type StandingsResponse = SquireResponse<Option<Standings<StandardScore>>>;
 
+/// The standings of a tournament with any scoring system
+pub type StandingsV2Response = SquireResponse<Option<Standings<TournamentScore>>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankHistoryRequest {
+    pub ident: TournamentIdentifier,
+    pub player: PlayerIdentifier,
+}
+
+/// A player's rank after each round of pairings. This is `None` if either the tournament or the
+/// player can't be found.
+pub type RankHistoryResponse = SquireResponse<Option<Vec<RankHistoryEntry>>>;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListOpsRequest {
     pub ident: TournamentIdentifier,
 }
 
-pub type ListOpsResponse = SquireResponse<Option<OpSlice>>;
+pub type ListOpsResponse = SquireResponse<Option<Result<OpSlice, TournamentError>>>;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SyncRequest {
@@ -50,7 +69,7 @@ pub struct SyncRequest {
     pub sync: OpSync,
 }
 
-pub type SyncResponse = SquireResponse<Option<SyncStatus>>;
+pub type SyncResponse = SquireResponse<Option<Result<SyncStatus, TournamentError>>>;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SyncImportRequest {
@@ -58,7 +77,8 @@ pub struct SyncImportRequest {
     pub sync: Synced,
 }
 
-pub type SyncImportResponse = SquireResponse<Option<Result<Synced, SyncStatus>>>;
+pub type SyncImportResponse =
+    SquireResponse<Option<Result<Result<Synced, SyncStatus>, TournamentError>>>;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RollbackRequest {
@@ -66,4 +86,21 @@ pub struct RollbackRequest {
     pub sync: Rollback,
 }
 
-pub type RollbackResponse = SquireResponse<Option<SyncStatus>>;
+pub type RollbackResponse = SquireResponse<Option<Result<SyncStatus, TournamentError>>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplyOpRequest {
+    pub ident: TournamentIdentifier,
+    pub op: TournOp,
+    pub reason: Option<String>,
+}
+
+pub type ApplyOpResponse = SquireResponse<Option<OpResult>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditRequest {
+    pub ident: TournamentIdentifier,
+    pub filter: AuditFilter,
+}
+
+pub type AuditResponse = SquireResponse<Option<Result<Vec<FullOp>, TournamentError>>>;